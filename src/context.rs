@@ -4,10 +4,11 @@
 
 use crate::error::Error;
 use crate::number::NumberConfig;
+use crate::program::{Halted, Profile};
 use crate::stack::{Heap, RpnStack};
 
 /// Execution context containing all runtime state
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Context {
     /// Main execution stack
     pub stack: RpnStack,
@@ -15,10 +16,30 @@ pub struct Context {
     /// Global heap for variables (sto/rcl)
     pub global_heap: Heap,
 
-    /// Local heap for loop/function scope variables
-    pub local_heap: Heap,
+    /// Frames of local variables, innermost last: one per running local-variable program or
+    /// FOR loop
+    pub local_frames: Vec<Heap>,
 
-    /// Error context tracking (function name where error occurred)
+    /// Number of local frames hidden from the running program, those of the programs calling
+    /// it by name
+    pub local_floor: usize,
+
+    /// Command lines suspended by HALT, the last one being resumed by CONT and SST
+    pub halted: Vec<Halted>,
+
+    /// Depth of the program calls around the object being run, including those of the programs
+    /// run by keywords such as EVAL or TEVEL, checked against `maxdepth`
+    pub call_depth: usize,
+
+    /// Number of program runs inside each other, programs run by keywords being run by a nested
+    /// run
+    pub nested_runs: usize,
+
+    /// Calls and instructions counted while PROFILE runs a program
+    pub profile: Option<Profile>,
+
+    /// Traceback of the last error not trapped: the source line of each program being run,
+    /// with a caret under the object being run
     pub error_context: Option<String>,
 
     /// Last error that occurred (for error/strerror commands)
@@ -27,6 +48,10 @@ pub struct Context {
     /// Flag to preserve last_error (set by error/strerror commands)
     pub preserve_last_error: bool,
 
+    /// Set under verified display by keywords acting outside of the context or giving a
+    /// different result on each run, the line is then not re-run
+    pub not_repeatable: bool,
+
     /// Number formatting and precision configuration
     pub config: NumberConfig,
 }
@@ -37,10 +62,16 @@ impl Context {
         Self {
             stack: RpnStack::new(),
             global_heap: Heap::new(),
-            local_heap: Heap::new(),
+            local_frames: Vec::new(),
+            local_floor: 0,
+            halted: Vec::new(),
+            call_depth: 0,
+            nested_runs: 0,
+            profile: None,
             error_context: None,
             last_error: None,
             preserve_last_error: false,
+            not_repeatable: false,
             config: NumberConfig::default(),
         }
     }
@@ -55,11 +86,57 @@ impl Context {
         self.error_context = Some(context);
     }
 
-    /// Find a variable in local heap, then global heap
+    /// Push a frame of local variables, hiding the outer variables of the same names
+    pub fn push_local_frame(&mut self, frame: Heap) {
+        self.local_frames.push(frame);
+    }
+
+    /// Pop the innermost frame of local variables
+    pub fn pop_local_frame(&mut self) {
+        self.local_frames.pop();
+    }
+
+    /// Number of frames of local variables
+    pub fn local_depth(&self) -> usize {
+        self.local_frames.len()
+    }
+
+    /// Drop the frames of local variables pushed since `local_depth` returned `depth`
+    pub fn truncate_local_frames(&mut self, depth: usize) {
+        self.local_frames.truncate(depth);
+    }
+
+    /// Frames of local variables visible from the running program, innermost last
+    pub fn visible_frames(&self) -> &[Heap] {
+        &self.local_frames[self.local_floor.min(self.local_frames.len())..]
+    }
+
+    /// Whether `name` is a local variable visible from the running program
+    pub fn is_local(&self, name: &str) -> bool {
+        self.visible_frames()
+            .iter()
+            .any(|frame| frame.contains(name))
+    }
+
+    /// Find a local variable, from the innermost frame outward
+    pub fn find_local_mut(&mut self, name: &str) -> Option<&mut crate::object::Object> {
+        let floor = self.local_floor.min(self.local_frames.len());
+        self.local_frames[floor..]
+            .iter_mut()
+            .rev()
+            .find_map(|frame| frame.recall_mut(name))
+    }
+
+    /// Find a variable in the visible local frames, innermost first, then in global heap
     /// Returns reference to object if found
     pub fn find_variable(&self, name: &str) -> Option<&crate::object::Object> {
-        // First check local heap
-        if let Some(obj) = self.local_heap.recall(name) {
+        // First check local frames
+        if let Some(obj) = self
+            .visible_frames()
+            .iter()
+            .rev()
+            .find_map(|frame| frame.recall(name))
+        {
             return Some(obj);
         }
 
@@ -67,11 +144,11 @@ impl Context {
         self.global_heap.recall(name)
     }
 
-    /// Find a mutable variable in local heap, then global heap
+    /// Find a mutable variable in the visible local frames, innermost first, then in global heap
     pub fn find_variable_mut(&mut self, name: &str) -> Option<&mut crate::object::Object> {
-        // Check local heap first
-        if self.local_heap.contains(name) {
-            return self.local_heap.recall_mut(name);
+        // Check local frames first
+        if self.is_local(name) {
+            return self.find_local_mut(name);
         }
 
         // Then check global heap
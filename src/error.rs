@@ -55,6 +55,9 @@ pub enum Error {
 
     #[error("test failed: {0}")]
     TestFailed(String),
+
+    #[error("{0}")]
+    UserError(String), // Raised by doerr with a message
 }
 
 impl Error {
@@ -63,7 +66,7 @@ impl Error {
     /// kOutOfRange=4, kUnknownVariable=5, kInternalError=6, kDeadlyError=7,
     /// kGoodbye=8, kNotImplemented=9, kNop=10, kSyntaxError=11,
     /// kDivByZero=12, kRuntimeError=13, kAbortCurrentEntry=14,
-    /// kOutOfMemory=15, kBadValue=16, kTestFailed=17
+    /// kOutOfMemory=15, kBadValue=16, kTestFailed=17, kUserError=18
     pub fn error_code(&self) -> i32 {
         match self {
             Error::UnknownError => 1,
@@ -84,9 +87,44 @@ impl Error {
             Error::OutOfMemory => 15,
             Error::BadValue(_) => 16,
             Error::TestFailed(_) => 17,
+            Error::UserError(_) => 18,
         }
     }
 
+    /// Build the error raised by `doerr` for an error code, None for codes that can't be raised
+    pub fn from_code(code: i32) -> Option<Error> {
+        let detail = || "doerr".to_string();
+        match code {
+            1 => Some(Error::UnknownError),
+            2 => Some(Error::MissingOperand),
+            3 => Some(Error::BadOperandType),
+            4 => Some(Error::OutOfRange),
+            5 => Some(Error::UnknownVariable(detail())),
+            6 => Some(Error::InternalError(detail())),
+            9 => Some(Error::NotImplemented(detail())),
+            11 => Some(Error::SyntaxError(detail())),
+            12 => Some(Error::DivByZero),
+            13 => Some(Error::RuntimeError(detail())),
+            15 => Some(Error::OutOfMemory),
+            16 => Some(Error::BadValue(detail())),
+            18 => Some(Error::UserError(detail())),
+            _ => None,
+        }
+    }
+
+    /// Whether an IFERR clause can trap this error
+    /// Quitting, editing, aborting the entry and deadly errors always stop the program
+    pub fn is_trappable(&self) -> bool {
+        !matches!(
+            self,
+            Error::DeadlyError(_)
+                | Error::Goodbye
+                | Error::Edit(_)
+                | Error::AbortCurrentEntry
+                | Error::TestFailed(_)
+        )
+    }
+
     /// Get the error name as a string (without details)
     pub fn error_name(&self) -> &'static str {
         Self::name_from_code(self.error_code())
@@ -113,6 +151,7 @@ impl Error {
             15 => "out of memory",
             16 => "bad value",
             17 => "test failed",
+            18 => "user error",
             _ => "unknown error code",
         }
     }
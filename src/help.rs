@@ -210,6 +210,26 @@ lazy_static! {
             args: &[("x", "number or complex")],
             example: "3.5 round",
         });
+        m.insert("rnd", CommandHelp {
+            name: "rnd",
+            description: "Round to n decimal places, or to -n significant digits if n is negative (half away from zero)",
+            syntax: "x n rnd",
+            args: &[
+                ("x", "number or complex"),
+                ("n", "integer, decimal places if positive, significant digits if negative"),
+            ],
+            example: "2 sqrt 3 rnd",
+        });
+        m.insert("trnc", CommandHelp {
+            name: "trnc",
+            description: "Truncate to n decimal places, or to -n significant digits if n is negative",
+            syntax: "x n trnc",
+            args: &[
+                ("x", "number or complex"),
+                ("n", "integer, decimal places if positive, significant digits if negative"),
+            ],
+            example: "2 sqrt -3 trnc",
+        });
         m.insert("ip", CommandHelp {
             name: "ip",
             description: "Extract integer part (truncate toward zero)",
@@ -238,6 +258,48 @@ lazy_static! {
             args: &[("x", "finite number")],
             example: "123.456 xpon",
         });
+        m.insert("ulp", CommandHelp {
+            name: "ulp",
+            description: "Unit in the last place, weight of the last mantissa bit of a number at its precision",
+            syntax: "x ulp",
+            args: &[("x", "finite nonzero number")],
+            example: "1 ulp",
+        });
+        m.insert("nextup", CommandHelp {
+            name: "nextup",
+            description: "Next float greater than a number at its precision",
+            syntax: "x nextup",
+            args: &[("x", "number")],
+            example: "1 nextup 1 -",
+        });
+        m.insert("nextdown", CommandHelp {
+            name: "nextdown",
+            description: "Next float less than a number at its precision",
+            syntax: "x nextdown",
+            args: &[("x", "number")],
+            example: "1 nextdown 1 -",
+        });
+        m.insert("prec?", CommandHelp {
+            name: "prec?",
+            description: "Precision in bits of a number or complex",
+            syntax: "x prec?",
+            args: &[("x", "number or complex")],
+            example: "64 prec pi prec?",
+        });
+        m.insert("exact", CommandHelp {
+            name: "exact",
+            description: "Exact decimal expansion of the stored binary number",
+            syntax: "x exact",
+            args: &[("x", "number")],
+            example: "0.1 exact",
+        });
+        m.insert("->bin", CommandHelp {
+            name: "->bin",
+            description: "Binary mantissa and power of 2 exponent of the stored number",
+            syntax: "x ->bin",
+            args: &[("x", "number")],
+            example: "0.1 ->bin",
+        });
 
         // Bitwise operations
         m.insert("&", CommandHelp {
@@ -267,6 +329,132 @@ lazy_static! {
             args: &[("x", "integer")],
             example: "0b1010 ~",
         });
+        m.insert("sl", CommandHelp {
+            name: "sl",
+            description: "Shift left by n bits",
+            syntax: "x n sl",
+            args: &[
+                ("x", "integer"),
+                ("n", "shift count, non-negative integer"),
+            ],
+            example: "0x0f 4 sl",
+        });
+        m.insert("sr", CommandHelp {
+            name: "sr",
+            description: "Logical shift right by n bits, negative values taken on the word size",
+            syntax: "x n sr",
+            args: &[
+                ("x", "integer"),
+                ("n", "shift count, non-negative integer"),
+            ],
+            example: "0xf0 4 sr",
+        });
+        m.insert("asr", CommandHelp {
+            name: "asr",
+            description: "Arithmetic shift right by n bits, sign is preserved",
+            syntax: "x n asr",
+            args: &[
+                ("x", "integer"),
+                ("n", "shift count, non-negative integer"),
+            ],
+            example: "-16 2 asr",
+        });
+        m.insert("rl", CommandHelp {
+            name: "rl",
+            description: "Rotate left by n bits within the word size",
+            syntax: "x n rl",
+            args: &[
+                ("x", "integer"),
+                ("n", "rotation count, non-negative integer"),
+            ],
+            example: "8 stws 0x81 1 rl",
+        });
+        m.insert("rr", CommandHelp {
+            name: "rr",
+            description: "Rotate right by n bits within the word size",
+            syntax: "x n rr",
+            args: &[
+                ("x", "integer"),
+                ("n", "rotation count, non-negative integer"),
+            ],
+            example: "8 stws 0x81 1 rr",
+        });
+        m.insert("bset", CommandHelp {
+            name: "bset",
+            description: "Set bit n",
+            syntax: "x n bset",
+            args: &[
+                ("x", "integer"),
+                ("n", "bit index, 0 is the least significant bit"),
+            ],
+            example: "0b1000 0 bset",
+        });
+        m.insert("bclr", CommandHelp {
+            name: "bclr",
+            description: "Clear bit n",
+            syntax: "x n bclr",
+            args: &[
+                ("x", "integer"),
+                ("n", "bit index, 0 is the least significant bit"),
+            ],
+            example: "0b1001 3 bclr",
+        });
+        m.insert("btst", CommandHelp {
+            name: "btst",
+            description: "Test bit n, push 1 if set or 0 if clear",
+            syntax: "x n btst",
+            args: &[
+                ("x", "integer"),
+                ("n", "bit index, 0 is the least significant bit"),
+            ],
+            example: "0b1001 3 btst",
+        });
+        m.insert("bfld", CommandHelp {
+            name: "bfld",
+            description: "Extract a bit field of len bits starting at bit pos",
+            syntax: "x pos len bfld",
+            args: &[
+                ("x", "integer"),
+                ("pos", "first bit index of the field"),
+                ("len", "field length in bits"),
+            ],
+            example: "0xdeadbeef 8 8 bfld",
+        });
+        m.insert("popcnt", CommandHelp {
+            name: "popcnt",
+            description: "Count the bits set within the word size",
+            syntax: "x popcnt",
+            args: &[("x", "integer")],
+            example: "0xff popcnt",
+        });
+        m.insert("clz", CommandHelp {
+            name: "clz",
+            description: "Count leading zeros within the word size",
+            syntax: "x clz",
+            args: &[("x", "integer")],
+            example: "32 stws 1 clz",
+        });
+        m.insert("ctz", CommandHelp {
+            name: "ctz",
+            description: "Count trailing zeros, word size for zero",
+            syntax: "x ctz",
+            args: &[("x", "integer")],
+            example: "0b1000 ctz",
+        });
+        m.insert("bswap", CommandHelp {
+            name: "bswap",
+            description: "Reverse the byte order within the word size",
+            syntax: "x bswap",
+            args: &[("x", "integer, word size must be a multiple of 8")],
+            example: "32 stws 0x12345678 bswap",
+        });
+        m.insert("brev", CommandHelp {
+            name: "brev",
+            description: "Reverse the bit order within the word size",
+            syntax: "x brev",
+            args: &[("x", "integer")],
+            example: "8 stws 0b1 brev",
+        });
 
         // Stack operations
         m.insert("swap", CommandHelp {
@@ -531,7 +719,7 @@ lazy_static! {
         });
         m.insert("arg", CommandHelp {
             name: "arg",
-            description: "Compute argument (angle) of a complex number in radians",
+            description: "Compute argument (angle) of a complex number in the angle mode",
             syntax: "z arg",
             args: &[("z", "complex number")],
             example: "(1,1) arg",
@@ -575,52 +763,212 @@ lazy_static! {
             example: "(1,1) r->p",
         });
 
+        // Encoding
+        m.insert("->f16bits", CommandHelp {
+            name: "->f16bits",
+            description: "Encode a number as an IEEE-754 binary16 bit pattern, shown in hex",
+            syntax: "x ->f16bits",
+            args: &[("x", "number, rounded to nearest even")],
+            example: "0.1 ->f16bits",
+        });
+        m.insert("->f32bits", CommandHelp {
+            name: "->f32bits",
+            description: "Encode a number as an IEEE-754 binary32 bit pattern, shown in hex",
+            syntax: "x ->f32bits",
+            args: &[("x", "number, rounded to nearest even")],
+            example: "0.1 ->f32bits",
+        });
+        m.insert("->f64bits", CommandHelp {
+            name: "->f64bits",
+            description: "Encode a number as an IEEE-754 binary64 bit pattern, shown in hex",
+            syntax: "x ->f64bits",
+            args: &[("x", "number, rounded to nearest even")],
+            example: "0.1 ->f64bits",
+        });
+        m.insert("->f128bits", CommandHelp {
+            name: "->f128bits",
+            description: "Encode a number as an IEEE-754 binary128 bit pattern, shown in hex",
+            syntax: "x ->f128bits",
+            args: &[("x", "number, rounded to nearest even")],
+            example: "0.1 ->f128bits",
+        });
+        m.insert("f16bits->", CommandHelp {
+            name: "f16bits->",
+            description: "Decode an IEEE-754 binary16 bit pattern into a number",
+            syntax: "bits f16bits->",
+            args: &[("bits", "non-negative integer of at most 16 bits")],
+            example: "0x3c00 f16bits->",
+        });
+        m.insert("f32bits->", CommandHelp {
+            name: "f32bits->",
+            description: "Decode an IEEE-754 binary32 bit pattern into a number",
+            syntax: "bits f32bits->",
+            args: &[("bits", "non-negative integer of at most 32 bits")],
+            example: "0x3f800000 f32bits->",
+        });
+        m.insert("f64bits->", CommandHelp {
+            name: "f64bits->",
+            description: "Decode an IEEE-754 binary64 bit pattern into a number",
+            syntax: "bits f64bits->",
+            args: &[("bits", "non-negative integer of at most 64 bits")],
+            example: "0x3ff0000000000000 f64bits->",
+        });
+        m.insert("f128bits->", CommandHelp {
+            name: "f128bits->",
+            description: "Decode an IEEE-754 binary128 bit pattern into a number",
+            syntax: "bits f128bits->",
+            args: &[("bits", "non-negative integer of at most 128 bits")],
+            example: "0x3fff0000000000000000000000000000 f128bits->",
+        });
+        m.insert("fsplit", CommandHelp {
+            name: "fsplit",
+            description: "Split an IEEE-754 bit pattern into sign, unbiased exponent and fraction",
+            syntax: "bits width fsplit",
+            args: &[
+                ("bits", "IEEE-754 bit pattern"),
+                ("width", "format width, 16, 32, 64 or 128"),
+            ],
+            example: "0x3fc00000 32 fsplit",
+        });
+        m.insert("->qmn", CommandHelp {
+            name: "->qmn",
+            description: "Quantize a number to Qm.n fixed point, push the code and a status (0 exact, 1 rounded, 2 saturated)",
+            syntax: "x m n ->qmn",
+            args: &[
+                ("x", "number"),
+                ("m", "integer bits, sign bit excluded"),
+                ("n", "fractional bits"),
+            ],
+            example: "0.3 1 15 ->qmn",
+        });
+        m.insert("qmn->", CommandHelp {
+            name: "qmn->",
+            description: "Convert a Qm.n fixed point code to a number",
+            syntax: "code m n qmn->",
+            args: &[
+                ("code", "two's complement code on 1+m+n bits"),
+                ("m", "integer bits, sign bit excluded"),
+                ("n", "fractional bits"),
+            ],
+            example: "0x2666 1 15 qmn->",
+        });
+
+        // Sexagesimal
+        m.insert("->hms", CommandHelp {
+            name: "->hms",
+            description: "Convert decimal hours to H.MMSS format",
+            syntax: "x ->hms",
+            args: &[("x", "decimal hours")],
+            example: "1.5 ->hms",
+        });
+        m.insert("hms->", CommandHelp {
+            name: "hms->",
+            description: "Convert H.MMSS format to decimal hours",
+            syntax: "hms hms->",
+            args: &[("hms", "hours in H.MMSS format")],
+            example: "1.3030 hms->",
+        });
+        m.insert("hms+", CommandHelp {
+            name: "hms+",
+            description: "Add two values in H.MMSS format",
+            syntax: "hms1 hms2 hms+",
+            args: &[
+                ("hms1", "hours in H.MMSS format"),
+                ("hms2", "hours in H.MMSS format"),
+            ],
+            example: "1.4530 2.3045 hms+",
+        });
+        m.insert("hms-", CommandHelp {
+            name: "hms-",
+            description: "Subtract two values in H.MMSS format",
+            syntax: "hms1 hms2 hms-",
+            args: &[
+                ("hms1", "hours in H.MMSS format"),
+                ("hms2", "hours in H.MMSS format"),
+            ],
+            example: "2.3045 1.4530 hms-",
+        });
+        m.insert("->dms", CommandHelp {
+            name: "->dms",
+            description: "Convert decimal degrees to D.MMSS format",
+            syntax: "x ->dms",
+            args: &[("x", "decimal degrees")],
+            example: "45.5125 ->dms",
+        });
+        m.insert("dms->", CommandHelp {
+            name: "dms->",
+            description: "Convert D.MMSS format to decimal degrees",
+            syntax: "dms dms->",
+            args: &[("dms", "angle in D.MMSS format")],
+            example: "45.3045 dms->",
+        });
+        m.insert("dms+", CommandHelp {
+            name: "dms+",
+            description: "Add two angles in D.MMSS format",
+            syntax: "dms1 dms2 dms+",
+            args: &[
+                ("dms1", "angle in D.MMSS format"),
+                ("dms2", "angle in D.MMSS format"),
+            ],
+            example: "10.3 20.45 dms+",
+        });
+        m.insert("dms-", CommandHelp {
+            name: "dms-",
+            description: "Subtract two angles in D.MMSS format",
+            syntax: "dms1 dms2 dms-",
+            args: &[
+                ("dms1", "angle in D.MMSS format"),
+                ("dms2", "angle in D.MMSS format"),
+            ],
+            example: "20.45 10.3 dms-",
+        });
+
         // Trigonometry
         m.insert("sin", CommandHelp {
             name: "sin",
-            description: "Compute sine (argument in radians)",
+            description: "Compute sine (argument in the angle mode)",
             syntax: "x sin",
-            args: &[("x", "number or complex, angle in radians")],
+            args: &[("x", "number in the angle mode, or complex in radians")],
             example: "pi 6 / sin",
         });
         m.insert("cos", CommandHelp {
             name: "cos",
-            description: "Compute cosine (argument in radians)",
+            description: "Compute cosine (argument in the angle mode)",
             syntax: "x cos",
-            args: &[("x", "number or complex, angle in radians")],
+            args: &[("x", "number in the angle mode, or complex in radians")],
             example: "pi 3 / cos",
         });
         m.insert("tan", CommandHelp {
             name: "tan",
-            description: "Compute tangent (argument in radians)",
+            description: "Compute tangent (argument in the angle mode)",
             syntax: "x tan",
-            args: &[("x", "number or complex, angle in radians")],
+            args: &[("x", "number in the angle mode, or complex in radians")],
             example: "pi 4 / tan",
         });
         m.insert("asin", CommandHelp {
             name: "asin",
-            description: "Compute arc sine (result in radians)",
+            description: "Compute arc sine (result in the angle mode)",
             syntax: "x asin",
             args: &[("x", "number or complex, -1 to 1 for real result")],
             example: "0.5 asin",
         });
         m.insert("acos", CommandHelp {
             name: "acos",
-            description: "Compute arc cosine (result in radians)",
+            description: "Compute arc cosine (result in the angle mode)",
             syntax: "x acos",
             args: &[("x", "number or complex, -1 to 1 for real result")],
             example: "0.5 acos",
         });
         m.insert("atan", CommandHelp {
             name: "atan",
-            description: "Compute arc tangent (result in radians)",
+            description: "Compute arc tangent (result in the angle mode)",
             syntax: "x atan",
             args: &[("x", "number or complex")],
             example: "1 atan",
         });
         m.insert("atan2", CommandHelp {
             name: "atan2",
-            description: "Two-argument arc tangent (result in radians)",
+            description: "Two-argument arc tangent (result in the angle mode)",
             syntax: "y x atan2",
             args: &[
                 ("y", "y-coordinate, number"),
@@ -914,6 +1262,20 @@ lazy_static! {
             args: &[],
             example: "if 1 2 < then 'less' end",
         });
+        m.insert("iferr", CommandHelp {
+            name: "iferr",
+            description: "Start an error trap: an error before then runs the error clause, else the optional else clause",
+            syntax: "iferr <trap-clause> then <error-clause> [else <normal-clause>] end",
+            args: &[],
+            example: "iferr 'x' sin then errm end",
+        });
+        m.insert("case", CommandHelp {
+            name: "case",
+            description: "Start a multi-way branch: the body of the first clause with a true test is run, else the default instructions",
+            syntax: "case <test> then <body> end ... [<default>] end",
+            args: &[],
+            example: "case x 0 < then 'neg' end x 0 == then 'zero' end 'pos' end",
+        });
         m.insert("then", CommandHelp {
             name: "then",
             description: "Evaluate condition and begin true branch",
@@ -930,7 +1292,7 @@ lazy_static! {
         });
         m.insert("end", CommandHelp {
             name: "end",
-            description: "End a control structure (if, while, do)",
+            description: "End a control structure (if, iferr, case and its clauses, while, do)",
             syntax: "... end",
             args: &[],
             example: "if 1 then 'yes' end",
@@ -986,10 +1348,10 @@ lazy_static! {
         });
         m.insert("step", CommandHelp {
             name: "step",
-            description: "End loop with custom step value",
+            description: "End loop with custom step value, a negative step counting down; the loop runs at least once",
             syntax: "for/start ... <step-value> step",
-            args: &[("step-value", "increment value, number")],
-            example: "0 10 for i i 2 step",
+            args: &[("step-value", "increment value, non-zero number")],
+            example: "10 0 for i i -2 step",
         });
         m.insert("while", CommandHelp {
             name: "while",
@@ -1019,6 +1381,27 @@ lazy_static! {
             args: &[],
             example: "do 1+ until dup 10 >= end",
         });
+        m.insert("break", CommandHelp {
+            name: "break",
+            description: "Leave the innermost for, start, while or do loop",
+            syntax: "... break ...",
+            args: &[],
+            example: "1 10 for i i if i 3 == then break end next",
+        });
+        m.insert("continue", CommandHelp {
+            name: "continue",
+            description: "Go on with the next iteration of the innermost for, start, while or do loop, not allowed in a step loop",
+            syntax: "... continue ...",
+            args: &[],
+            example: "1 5 for i if i 2 == then continue end i next",
+        });
+        m.insert("return", CommandHelp {
+            name: "return",
+            description: "Leave the program, or the command line, before its end",
+            syntax: "... return ...",
+            args: &[],
+            example: "<< -> n << if n 0 < then 'negative' return end n sqrt >> >>",
+        });
 
         // Programs
         m.insert("eval", CommandHelp {
@@ -1035,6 +1418,83 @@ lazy_static! {
             args: &[("var1 var2 ...", "unquoted symbols, local variable names")],
             example: "<< -> x y << x y + >> >>",
         });
+        m.insert("maxdepth", CommandHelp {
+            name: "maxdepth",
+            description: "Set the maximum number of programs being run, calling each other, before a runtime error",
+            syntax: "n maxdepth",
+            args: &[("n", "maximum depth, 1 to 1000000, default 10000")],
+            example: "100000 maxdepth",
+        });
+        m.insert("halt", CommandHelp {
+            name: "halt",
+            description: "Suspend the running program and get back to the prompt, the stack and local variables staying as they are",
+            syntax: "... halt ...",
+            args: &[],
+            example: "<< -> x << x sq halt x 1 + >> >>",
+        });
+        m.insert("cont", CommandHelp {
+            name: "cont",
+            description: "Resume the halted program up to its end or its next halt",
+            syntax: "cont",
+            args: &[],
+            example: "cont",
+        });
+        m.insert("sst", CommandHelp {
+            name: "sst",
+            description: "Run the next object of the halted program, stepping into the programs it calls",
+            syntax: "sst",
+            args: &[],
+            example: "sst",
+        });
+        m.insert("sst↓", CommandHelp {
+            name: "sst↓",
+            description: "Run the next object of the halted program, with the programs it calls",
+            syntax: "sst↓",
+            args: &[],
+            example: "sst↓",
+        });
+        m.insert("kill", CommandHelp {
+            name: "kill",
+            description: "Abandon the halted programs and their local variables",
+            syntax: "kill",
+            args: &[],
+            example: "kill",
+        });
+        m.insert("trace", CommandHelp {
+            name: "trace",
+            description: "Print each object run, with the stack depth and the top stack values",
+            syntax: "trace",
+            args: &[],
+            example: "trace << 2 3 + >> eval",
+        });
+        m.insert("notrace", CommandHelp {
+            name: "notrace",
+            description: "Run objects without printing them",
+            syntax: "notrace",
+            args: &[],
+            example: "notrace",
+        });
+        m.insert("profile", CommandHelp {
+            name: "profile",
+            description: "Run a program and print the calls and cumulative time of each keyword, and the number of objects run",
+            syntax: "program profile",
+            args: &[("program", "program, or name of a variable holding one")],
+            example: "<< 1 1000 for k k sq next >> profile",
+        });
+        m.insert("tevel", CommandHelp {
+            name: "tevel",
+            description: "Run a program and push its run time in seconds, after its results",
+            syntax: "program tevel",
+            args: &[("program", "program, or name of a variable holding one")],
+            example: "'fib' tevel",
+        });
+        m.insert("teval", CommandHelp {
+            name: "teval",
+            description: "Alias for tevel",
+            syntax: "program teval",
+            args: &[("program", "program, or name of a variable holding one")],
+            example: "'fib' teval",
+        });
 
         // Configuration
         m.insert("std", CommandHelp {
@@ -1058,6 +1518,90 @@ lazy_static! {
             args: &[("n", "number of significant digits, positive integer")],
             example: "6 sci",
         });
+        m.insert("eng", CommandHelp {
+            name: "eng",
+            description: "Set engineering notation, exponent multiple of 3, with n+1 significant digits",
+            syntax: "n eng",
+            args: &[("n", "digits after the first significant digit, positive integer")],
+            example: "3 eng",
+        });
+        m.insert("si", CommandHelp {
+            name: "si",
+            description: "Set engineering notation with SI prefixes instead of exponents",
+            syntax: "n si",
+            args: &[("n", "digits after the first significant digit, positive integer")],
+            example: "1 si",
+        });
+        m.insert("grp", CommandHelp {
+            name: "grp",
+            description: "Group displayed digits: thousands in decimal, 4 digits in other bases",
+            syntax: "grp",
+            args: &[],
+            example: "grp 1234567.8912",
+        });
+        m.insert("nogrp", CommandHelp {
+            name: "nogrp",
+            description: "Display digits without group separators",
+            syntax: "nogrp",
+            args: &[],
+            example: "nogrp",
+        });
+        m.insert("dot", CommandHelp {
+            name: "dot",
+            description: "Use '.' as decimal separator",
+            syntax: "dot",
+            args: &[],
+            example: "dot",
+        });
+        m.insert("comma", CommandHelp {
+            name: "comma",
+            description: "Use ',' as decimal separator",
+            syntax: "comma",
+            args: &[],
+            example: "comma 1.5",
+        });
+        m.insert("verify", CommandHelp {
+            name: "verify",
+            description: "Re-run each command line at increasing precision until displayed digits are stable, uncertain digits follow a ~",
+            syntax: "verify",
+            args: &[],
+            example: "verify 1e30 1 + 1e30 -",
+        });
+        m.insert("noverify", CommandHelp {
+            name: "noverify",
+            description: "Display digits as computed, without verification",
+            syntax: "noverify",
+            args: &[],
+            example: "noverify",
+        });
+        m.insert("pair", CommandHelp {
+            name: "pair",
+            description: "Display complex numbers as (re,im) pairs",
+            syntax: "pair",
+            args: &[],
+            example: "(3,4) pair",
+        });
+        m.insert("rect", CommandHelp {
+            name: "rect",
+            description: "Display complex numbers in rectangular form a+bi",
+            syntax: "rect",
+            args: &[],
+            example: "(3,4) rect",
+        });
+        m.insert("rectj", CommandHelp {
+            name: "rectj",
+            description: "Display complex numbers in rectangular form a+bj",
+            syntax: "rectj",
+            args: &[],
+            example: "(3,4) rectj",
+        });
+        m.insert("polar", CommandHelp {
+            name: "polar",
+            description: "Display complex numbers in polar form (r∠θ), θ in the current angle mode",
+            syntax: "polar",
+            args: &[],
+            example: "deg (3,4) polar",
+        });
         m.insert("prec", CommandHelp {
             name: "prec",
             description: "Set floating-point precision in bits",
@@ -1065,9 +1609,72 @@ lazy_static! {
             args: &[("n", "precision in bits, 2 to 100000")],
             example: "256 prec",
         });
+        m.insert("deg", CommandHelp {
+            name: "deg",
+            description: "Set angle mode to degrees for trigonometric functions",
+            syntax: "deg",
+            args: &[],
+            example: "deg 90 sin",
+        });
+        m.insert("rad", CommandHelp {
+            name: "rad",
+            description: "Set angle mode to radians for trigonometric functions",
+            syntax: "rad",
+            args: &[],
+            example: "rad pi 2 / sin",
+        });
+        m.insert("grad", CommandHelp {
+            name: "grad",
+            description: "Set angle mode to gradians for trigonometric functions",
+            syntax: "grad",
+            args: &[],
+            example: "grad 100 sin",
+        });
+        m.insert("cmplx", CommandHelp {
+            name: "cmplx",
+            description: "Give complex results for reals out of the real domain (default)",
+            syntax: "cmplx",
+            args: &[],
+            example: "cmplx -1 ln",
+        });
+        m.insert("real", CommandHelp {
+            name: "real",
+            description: "Give nan for reals out of the real domain",
+            syntax: "real",
+            args: &[],
+            example: "real -1 sqrt",
+        });
+        m.insert("rnear", CommandHelp {
+            name: "rnear",
+            description: "Real operations round to nearest, ties to even (default)",
+            syntax: "rnear",
+            args: &[],
+            example: "rnear 1 3 /",
+        });
+        m.insert("rup", CommandHelp {
+            name: "rup",
+            description: "Real operations round toward +inf",
+            syntax: "rup",
+            args: &[],
+            example: "rup 1 3 /",
+        });
+        m.insert("rdown", CommandHelp {
+            name: "rdown",
+            description: "Real operations round toward -inf",
+            syntax: "rdown",
+            args: &[],
+            example: "rdown 1 3 /",
+        });
+        m.insert("rzero", CommandHelp {
+            name: "rzero",
+            description: "Real operations round toward zero",
+            syntax: "rzero",
+            args: &[],
+            example: "rzero -1 3 /",
+        });
         m.insert("default", CommandHelp {
             name: "default",
-            description: "Reset display mode and precision to defaults",
+            description: "Reset display mode, precision, word size, angle mode, complex results, rounding, modulus, grouping, decimal separator, verified display, complex display, input base, maximum depth and trace to defaults",
             syntax: "default",
             args: &[],
             example: "default",
@@ -1103,6 +1710,41 @@ lazy_static! {
             ],
             example: "100 8 base",
         });
+        m.insert("ibase", CommandHelp {
+            name: "ibase",
+            description: "Set the base of numbers entered without a 0x, 0b or Nb prefix, shown in the prompt when not 10",
+            syntax: "b ibase",
+            args: &[("b", "input base, 2 to 62")],
+            example: "16 ibase",
+        });
+        m.insert("stws", CommandHelp {
+            name: "stws",
+            description: "Set the word size in bits used by rotates, byte swap and bit counts",
+            syntax: "n stws",
+            args: &[("n", "word size, 1 to 65536, default 64")],
+            example: "32 stws",
+        });
+        m.insert("rcws", CommandHelp {
+            name: "rcws",
+            description: "Push the current word size in bits",
+            syntax: "rcws",
+            args: &[],
+            example: "rcws",
+        });
+        m.insert("modsto", CommandHelp {
+            name: "modsto",
+            description: "Set the modulus of integer arithmetic, 0 turns modular mode off",
+            syntax: "n modsto",
+            args: &[("n", "modulus, integer >= 2, or 0")],
+            example: "97 modsto",
+        });
+        m.insert("modrcl", CommandHelp {
+            name: "modrcl",
+            description: "Recall the modulus of integer arithmetic, 0 when modular mode is off",
+            syntax: "modrcl",
+            args: &[],
+            example: "modrcl",
+        });
         m.insert("type", CommandHelp {
             name: "type",
             description: "Push the type name of the top stack item",
@@ -1168,6 +1810,34 @@ lazy_static! {
             args: &[],
             example: "strerror",
         });
+        m.insert("errn", CommandHelp {
+            name: "errn",
+            description: "Push the code of the last error, 0 when none, as error",
+            syntax: "errn",
+            args: &[],
+            example: "iferr drop then errn end",
+        });
+        m.insert("errm", CommandHelp {
+            name: "errm",
+            description: "Push the full message of the last error, empty when none",
+            syntax: "errm",
+            args: &[],
+            example: "iferr drop then errm end",
+        });
+        m.insert("err0", CommandHelp {
+            name: "err0",
+            description: "Clear the last error, errn gives 0 afterwards",
+            syntax: "err0",
+            args: &[],
+            example: "err0",
+        });
+        m.insert("doerr", CommandHelp {
+            name: "doerr",
+            description: "Raise an error from its code, or a user error (code 18) from a message",
+            syntax: "code doerr, 'message' doerr",
+            args: &[("code", "error code or message")],
+            example: "'too hot' doerr",
+        });
         m.insert("test", CommandHelp {
             name: "test",
             description: "Run a test file",
@@ -2,22 +2,32 @@
 // Lexer for RPN calculator
 
 use crate::error::{Error, Result};
-use rug::ops::CompleteRound;
+use crate::number::{
+    si_prefix_exponent, AngleMode, NumberConfig, BASE_GROUP_SEPARATOR, DECIMAL_GROUP_SEPARATOR,
+};
+use crate::operations::trig::{cos_angle, sin_angle};
+use crate::operations::{BRANCHES, KEYWORDS};
+use rug::float::Round;
+use rug::ops::{CompleteRound, Pow};
 use rug::Float;
 use rug::Integer;
 
 /// Parse a number string in bases 37-62
 /// rug's parse_radix only supports up to base 36, so we implement custom parsing
 /// Digit mapping: 0-9 (0-9), A-Z (10-35), a-z (36-61)
-fn parse_base_37_to_62(digits_str: &str, base: i32) -> Option<Float> {
+fn parse_base_37_to_62(digits_str: &str, base: i32, precision: u32) -> Option<Float> {
     if base < 37 || base > 62 {
         return None;
     }
 
     let mut result = Integer::from(0);
     let base_int = Integer::from(base);
+    let (int_str, frac_str) = digits_str.split_once('.').unwrap_or((digits_str, ""));
+    if int_str.is_empty() && frac_str.is_empty() {
+        return None;
+    }
 
-    for ch in digits_str.chars() {
+    for ch in int_str.chars().chain(frac_str.chars()) {
         let digit_value = match ch {
             '0'..='9' => (ch as i32) - ('0' as i32),
             'A'..='Z' => (ch as i32) - ('A' as i32) + 10,
@@ -33,7 +43,32 @@ fn parse_base_37_to_62(digits_str: &str, base: i32) -> Option<Float> {
         result += digit_value;
     }
 
-    Some(Float::with_val(128, result))
+    // Fractional digits divide the value by base^digits
+    let scale = base_int.pow(frac_str.len() as u32);
+    Some(Float::with_val(
+        precision,
+        rug::Rational::from((result, scale)),
+    ))
+}
+
+/// Parse a number string in base 2 or 16 with an optional binary exponent: 1.8p3 = 1.5 * 2^3
+/// rug's parse_radix only knows the `@` exponent (a power of the base)
+fn parse_radix_binary_exponent(digits_str: &str, radix: i32, precision: u32) -> Option<Float> {
+    let (mantissa, exponent) = match digits_str.split_once(['p', 'P']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (digits_str, 0),
+    };
+    let value = Float::parse_radix(mantissa, radix)
+        .ok()?
+        .complete(precision);
+    Some(value << exponent)
+}
+
+/// Position of a token in its source, in characters from the start of the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
 }
 
 /// Token types produced by lexical analysis
@@ -67,14 +102,63 @@ pub enum Token {
 /// Lexer for tokenizing RPN expressions
 pub struct Lexer;
 
+/// Remove digit group separators found between two digits and, with a `,` decimal separator,
+/// read a decimal comma as a point, so that numbers displayed with `grp` or `comma` can be
+/// entered back
+fn strip_digit_separators(word: &str, decimal_separator: char) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    let mut result = String::with_capacity(word.len());
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch == BASE_GROUP_SEPARATOR || ch == DECIMAL_GROUP_SEPARATOR {
+            let between_digits = i > 0
+                && chars[i - 1].is_ascii_alphanumeric()
+                && chars.get(i + 1).is_some_and(|c| c.is_ascii_alphanumeric());
+            if between_digits {
+                continue;
+            }
+        }
+        result.push(if ch == ',' && decimal_separator == ',' {
+            '.'
+        } else {
+            ch
+        });
+    }
+    result
+}
+
 impl Lexer {
-    /// Analyze source string and produce tokens
+    /// Analyze source string and produce tokens, numbers being read with 128 bits in decimal
     pub fn analyze(source: &str) -> Result<Vec<Token>> {
+        Self::analyze_with_config(source, &NumberConfig::default())
+    }
+
+    /// Analyze source string and produce tokens, numbers being read with `config` precision
+    /// and unprefixed numbers in `config` input base
+    pub fn analyze_with_config(source: &str, config: &NumberConfig) -> Result<Vec<Token>> {
+        let tokens = Self::analyze_with_spans(source, config)?;
+        Ok(tokens.into_iter().map(|(token, _)| token).collect())
+    }
+
+    /// Analyze source string and produce tokens with their position in the source
+    /// The tokens of a word split in several tokens share the span of the word
+    pub fn analyze_with_spans(source: &str, config: &NumberConfig) -> Result<Vec<(Token, Span)>> {
         let mut tokens = Vec::new();
+        let mut spans = Vec::new();
         let mut chars = source.chars().peekable();
         let mut position = 0;
+        let mut start = 0;
 
         while let Some(ch) = chars.peek() {
+            // The tokens pushed by the previous iteration span from its start to here
+            spans.resize(
+                tokens.len(),
+                Span {
+                    start,
+                    end: position,
+                },
+            );
+            start = position;
+
             match ch {
                 // Skip whitespace
                 ' ' | '\t' | '\n' | '\r' => {
@@ -170,7 +254,7 @@ impl Lexer {
                         }
                     }
                     // Not a standalone ->, parse as word (could be negative number or ->str etc.)
-                    let word_tokens = Self::parse_word(&mut chars, &mut position)?;
+                    let word_tokens = Self::parse_word(&mut chars, &mut position, config)?;
                     tokens.extend(word_tokens);
                 }
 
@@ -180,7 +264,7 @@ impl Lexer {
                     let saved_chars = chars.clone();
                     let saved_pos = position;
 
-                    match Self::parse_complex(&mut chars, &mut position) {
+                    match Self::parse_complex(&mut chars, &mut position, config) {
                         Ok(token) => tokens.push(token),
                         Err(_) => {
                             // Not a valid complex number, collect as a symbol
@@ -236,13 +320,20 @@ impl Lexer {
 
                 // Everything else: numbers, keywords, or unknown symbols
                 _ => {
-                    let word_tokens = Self::parse_word(&mut chars, &mut position)?;
+                    let word_tokens = Self::parse_word(&mut chars, &mut position, config)?;
                     tokens.extend(word_tokens);
                 }
             }
         }
-
-        Ok(tokens)
+        spans.resize(
+            tokens.len(),
+            Span {
+                start,
+                end: position,
+            },
+        );
+
+        Ok(tokens.into_iter().zip(spans).collect())
     }
 
     /// Parse a quoted symbol '...'
@@ -373,7 +464,10 @@ impl Lexer {
     }
 
     /// Parse a number string with base support (helper for complex numbers)
-    fn parse_number_with_base(num_str: &str) -> Result<(Float, u8)> {
+    fn parse_number_with_base(num_str: &str, config: &NumberConfig) -> Result<(Float, u8)> {
+        let precision = config.precision_bits;
+        let num_str: &str = &strip_digit_separators(num_str, config.decimal_separator);
+
         // Check for leading minus sign
         let (is_negative, num_without_sign) = if num_str.starts_with('-') {
             (true, &num_str[1..])
@@ -384,39 +478,26 @@ impl Lexer {
         };
 
         // Try to parse with base prefix (using num_without_sign consistently)
-        // Reject fractional parts in hex/bin/base (no decimal points allowed)
         let (mut value, base) = if num_without_sign.starts_with("0x")
             || num_without_sign.starts_with("0X")
         {
-            // Hex - reject if contains decimal point
+            // Hex, with optional fraction and binary exponent: 0x1.8p3
             let hex_str = &num_without_sign[2..];
-            if hex_str.contains('.') {
-                return Err(Error::SyntaxError(format!("invalid hex number: {}", num_str)));
-            }
-            let val = Float::parse_radix(hex_str, 16)
-                .map_err(|_| Error::SyntaxError(format!("invalid hex number: {}", num_str)))?
-                .complete(128);
+            let val = parse_radix_binary_exponent(hex_str, 16, precision)
+                .ok_or_else(|| Error::SyntaxError(format!("invalid hex number: {}", num_str)))?;
             (val, 16)
         } else if num_without_sign.starts_with("0b") || num_without_sign.starts_with("0B") {
-            // Binary - reject if contains decimal point
+            // Binary, with optional fraction and exponent: 0b1.1p3
             let bin_str = &num_without_sign[2..];
-            if bin_str.contains('.') {
-                return Err(Error::SyntaxError(format!("invalid binary number: {}", num_str)));
-            }
-            let val = Float::parse_radix(bin_str, 2)
-                .map_err(|_| Error::SyntaxError(format!("invalid binary number: {}", num_str)))?
-                .complete(128);
+            let val = parse_radix_binary_exponent(bin_str, 2, precision)
+                .ok_or_else(|| Error::SyntaxError(format!("invalid binary number: {}", num_str)))?;
             (val, 2)
         } else if let Some(b_pos) = num_without_sign.find(|c| c == 'b' || c == 'B') {
-            // Arbitrary base: Nb... or NB... - reject if contains decimal point
+            // Arbitrary base: Nb... or NB..., with optional fraction
             if b_pos > 0 && b_pos < num_without_sign.len() - 1 {
                 let base_str = &num_without_sign[..b_pos];
                 let digits_str = &num_without_sign[b_pos + 1..];
 
-                if digits_str.contains('.') {
-                    return Err(Error::SyntaxError(format!("invalid base number: {}", num_str)));
-                }
-
                 if let Ok(base) = base_str.parse::<i32>() {
                     if base >= 2 && base <= 62 {
                         let val = if base <= 36 {
@@ -427,9 +508,9 @@ impl Lexer {
                                         base, num_str
                                     ))
                                 })?
-                                .complete(128)
+                                .complete(precision)
                         } else {
-                            parse_base_37_to_62(digits_str, base).ok_or_else(|| {
+                            parse_base_37_to_62(digits_str, base, precision).ok_or_else(|| {
                                 Error::SyntaxError(format!(
                                     "invalid base {} number: {}",
                                     base, num_str
@@ -443,28 +524,28 @@ impl Lexer {
                             .map_err(|_| {
                                 Error::SyntaxError(format!("invalid number: {}", num_str))
                             })?
-                            .complete(128);
+                            .complete(precision);
                         (val, 10)
                     }
                 } else {
                     // Not a valid base number, parse as decimal
                     let val = Float::parse(num_without_sign)
                         .map_err(|_| Error::SyntaxError(format!("invalid number: {}", num_str)))?
-                        .complete(128);
+                        .complete(precision);
                     (val, 10)
                 }
             } else {
                 // Not a valid base format, parse as decimal
                 let val = Float::parse(num_without_sign)
                     .map_err(|_| Error::SyntaxError(format!("invalid number: {}", num_str)))?
-                    .complete(128);
+                    .complete(precision);
                 (val, 10)
             }
         } else {
             // Decimal (use num_without_sign to avoid double negation)
             let val = Float::parse(num_without_sign)
                 .map_err(|_| Error::SyntaxError(format!("invalid number: {}", num_str)))?
-                .complete(128);
+                .complete(precision);
             (val, 10)
         };
 
@@ -480,6 +561,7 @@ impl Lexer {
     fn parse_complex(
         chars: &mut std::iter::Peekable<std::str::Chars>,
         position: &mut usize,
+        config: &NumberConfig,
     ) -> Result<Token> {
         // Skip opening paren
         chars.next();
@@ -497,8 +579,17 @@ impl Lexer {
             *position += 1;
         }
 
-        // Split by comma
-        let parts: Vec<&str> = content.split(',').collect();
+        // Polar form (r∠θ), θ in the angle mode or suffixed with ° or ᵍ
+        if let Some((r_str, theta_str)) = content.split_once('∠') {
+            return Self::parse_polar(r_str.trim(), theta_str.trim(), config);
+        }
+
+        // Split by comma, or by semicolon when parts use a decimal comma
+        let parts: Vec<&str> = if content.contains(';') {
+            content.split(';').collect()
+        } else {
+            content.split(',').collect()
+        };
         if parts.len() != 2 {
             return Err(Error::SyntaxError(
                 "complex number must have format (re,im)".to_string(),
@@ -509,10 +600,68 @@ impl Lexer {
         let re_str = parts[0].trim();
         let im_str = parts[1].trim();
 
-        let (re, re_base) = Self::parse_number_with_base(re_str)?;
-        let (im, im_base) = Self::parse_number_with_base(im_str)?;
+        let (re, re_base) = Self::parse_number_with_base(re_str, config)?;
+        let (im, im_base) = Self::parse_number_with_base(im_str, config)?;
+
+        Ok(Token::Complex {
+            re,
+            im,
+            re_base,
+            im_base,
+        })
+    }
+
+    /// Build a complex token from a modulus and an angle, e.g. 5 and 53.13°
+    fn parse_polar(r_str: &str, theta_str: &str, config: &NumberConfig) -> Result<Token> {
+        let (theta_str, mode) = if let Some(deg) = theta_str.strip_suffix('°') {
+            (deg, AngleMode::Deg)
+        } else if let Some(grad) = theta_str.strip_suffix('ᵍ') {
+            (grad, AngleMode::Grad)
+        } else {
+            (theta_str, config.angle_mode)
+        };
+        let (r, re_base) = Self::parse_number_with_base(r_str, config)?;
+        let (theta, _) = Self::parse_number_with_base(theta_str.trim_end(), config)?;
 
+        let re = cos_angle(theta.clone(), mode, Round::Nearest) * &r;
+        let im = sin_angle(theta, mode, Round::Nearest) * &r;
         Ok(Token::Complex {
+            re,
+            im,
+            re_base,
+            im_base: re_base,
+        })
+    }
+
+    /// Parse a rectangular complex like 3+4i, 3-4j or 4i
+    /// Returns None if the word doesn't match this pattern
+    fn parse_rect_complex(word: &str, config: &NumberConfig) -> Option<Token> {
+        let body = word.strip_suffix(['i', 'j'])?;
+
+        // Split at the rightmost sign giving two valid numbers, so that 1e-3+2e-3i works
+        let split = body
+            .char_indices()
+            .rev()
+            .filter(|&(i, ch)| i > 0 && (ch == '+' || ch == '-'))
+            .find_map(|(i, _)| {
+                let im_str = match &body[i..] {
+                    "+" => "1",
+                    "-" => "-1",
+                    im => im,
+                };
+                let re = Self::parse_number_with_base(&body[..i], config).ok()?;
+                let im = Self::parse_number_with_base(im_str, config).ok()?;
+                Some((re, im))
+            });
+        let ((re, re_base), (im, im_base)) = match split {
+            Some(parts) => parts,
+            // Pure imaginary: 4i
+            None => {
+                let im = Self::parse_number_with_base(body, config).ok()?;
+                ((Float::with_val(config.precision_bits, 0), 10), im)
+            }
+        };
+        Some(Token::Complex {
             re,
             im,
             re_base,
@@ -528,13 +677,14 @@ impl Lexer {
     fn parse_word(
         chars: &mut std::iter::Peekable<std::str::Chars>,
         position: &mut usize,
+        config: &NumberConfig,
     ) -> Result<Vec<Token>> {
         let mut word = String::new();
 
         // Collect non-whitespace characters
         // Break on delimiters but NOT on operators (to allow sto+, sto-, etc.)
         while let Some(&ch) = chars.peek() {
-            if ch.is_whitespace()
+            if (ch.is_whitespace() && ch != DECIMAL_GROUP_SEPARATOR)
                 || ch == '"'
                 || ch == '\''
                 || ch == '('
@@ -553,13 +703,17 @@ impl Lexer {
         }
 
         // Try to parse as number
-        if let Ok(token) = Self::parse_number_token(&word) {
+        if let Ok(token) = Self::parse_number_in_base(&word, config) {
+            return Ok(vec![token]);
+        }
+
+        if let Some(token) = Self::parse_rect_complex(&word, config) {
             return Ok(vec![token]);
         }
 
         // Check if word ends with sticky operators that should be split off
         // e.g., "2+" -> ["2", "+"], "0xff&" -> ["0xff", "&"]
-        if let Some(tokens) = Self::try_split_number_and_operators(&word) {
+        if let Some(tokens) = Self::try_split_number_and_operators(&word, config) {
             return Ok(tokens);
         }
 
@@ -573,7 +727,7 @@ impl Lexer {
 
     /// Try to split a word into a number followed by operators
     /// Returns None if the word doesn't match this pattern
-    fn try_split_number_and_operators(word: &str) -> Option<Vec<Token>> {
+    fn try_split_number_and_operators(word: &str, config: &NumberConfig) -> Option<Vec<Token>> {
         // Find where the trailing operators start
         let mut split_pos = word.len();
 
@@ -595,7 +749,7 @@ impl Lexer {
         let operators_part = &word[split_pos..];
 
         // Try to parse the number part
-        if let Ok(num_token) = Self::parse_number_token(number_part) {
+        if let Ok(num_token) = Self::parse_number_in_base(number_part, config) {
             let mut tokens = vec![num_token];
 
             // Add each operator as a separate token
@@ -612,8 +766,38 @@ impl Lexer {
         None
     }
 
+    /// Try to parse a word as a number, unprefixed digits being read in `input_base`
+    /// Keywords and numbers with an explicit 0x or 0b prefix are not affected: in base 16, ff is
+    /// 0xff but dec is the keyword and 0b11 is binary. An Nb prefix is only one when the word
+    /// can't be read in the input base: in base 16, 2b10 is 0x2b10, in base 8 it is binary
+    fn parse_number_in_base(word: &str, config: &NumberConfig) -> Result<Token> {
+        let input_base = config.input_base;
+        if input_base != 10 && !KEYWORDS.contains_key(word) && !BRANCHES.contains_key(word) {
+            let (sign, digits) = match word.strip_prefix(['-', '+']) {
+                Some(digits) => (&word[..1], digits),
+                None => ("", word),
+            };
+            if !Self::has_base_prefix(digits) {
+                let prefixed = format!("{}{}b{}", sign, input_base, digits);
+                if let Ok(token) = Self::parse_number_token(&prefixed, config) {
+                    return Ok(token);
+                }
+            }
+        }
+        Self::parse_number_token(word, config)
+    }
+
+    /// Whether digits start with a 0x or 0b base prefix
+    fn has_base_prefix(digits: &str) -> bool {
+        let lower = digits.to_ascii_lowercase();
+        lower.starts_with("0x") || lower.starts_with("0b")
+    }
+
     /// Try to parse a word as a number with base support
-    fn parse_number_token(word: &str) -> Result<Token> {
+    fn parse_number_token(word: &str, config: &NumberConfig) -> Result<Token> {
+        let precision = config.precision_bits;
+        let word: &str = &strip_digit_separators(word, config.decimal_separator);
+
         // Check for leading minus sign
         let (is_negative, word_without_sign) = if word.starts_with('-') {
             (true, &word[1..])
@@ -623,8 +807,7 @@ impl Lexer {
             (false, word)
         };
 
-        // Hex: 0x... or 0x-...
-        // Reject if contains decimal point (treat as symbol)
+        // Hex: 0x... or 0x-..., with optional fraction and binary exponent: 0x1.8p3
         if word_without_sign.starts_with("0x") || word_without_sign.starts_with("0X") {
             let hex_str = &word_without_sign[2..];
             // Handle 0x-... format (minus after prefix)
@@ -636,24 +819,19 @@ impl Lexer {
                 (false, hex_str)
             };
 
-            // Reject fractional hex numbers (treat as symbol)
-            if !hex_digits.contains('.') {
-                if let Ok(value) = Float::parse_radix(hex_digits, 16) {
-                    let mut result = value.complete(128);
-                    if is_negative ^ hex_negative {
-                        // XOR: negate if exactly one minus sign
-                        result = -result;
-                    }
-                    return Ok(Token::Number {
-                        value: result,
-                        base: 16,
-                    });
+            if let Some(mut result) = parse_radix_binary_exponent(hex_digits, 16, precision) {
+                if is_negative ^ hex_negative {
+                    // XOR: negate if exactly one minus sign
+                    result = -result;
                 }
+                return Ok(Token::Number {
+                    value: result,
+                    base: 16,
+                });
             }
         }
 
-        // Binary: 0b... or 0b-...
-        // Reject if contains decimal point (treat as symbol)
+        // Binary: 0b... or 0b-..., with optional fraction and exponent: 0b1.1p3
         if word_without_sign.starts_with("0b") || word_without_sign.starts_with("0B") {
             let bin_str = &word_without_sign[2..];
             // Handle 0b-... format (minus after prefix)
@@ -665,25 +843,20 @@ impl Lexer {
                 (false, bin_str)
             };
 
-            // Reject fractional binary numbers (treat as symbol)
-            if !bin_digits.contains('.') {
-                if let Ok(value) = Float::parse_radix(bin_digits, 2) {
-                    let mut result = value.complete(128);
-                    if is_negative ^ bin_negative {
-                        // XOR: negate if exactly one minus sign
-                        result = -result;
-                    }
-                    return Ok(Token::Number {
-                        value: result,
-                        base: 2,
-                    });
+            if let Some(mut result) = parse_radix_binary_exponent(bin_digits, 2, precision) {
+                if is_negative ^ bin_negative {
+                    // XOR: negate if exactly one minus sign
+                    result = -result;
                 }
+                return Ok(Token::Number {
+                    value: result,
+                    base: 2,
+                });
             }
         }
 
         // Arbitrary base: Nb... or Nb-...
-        // Format: <base>b<digits>
-        // Reject if contains decimal point (treat as symbol)
+        // Format: <base>b<digits>[.<digits>]
         if let Some(b_pos) = word_without_sign.find(|c| c == 'b' || c == 'B') {
             if b_pos > 0 && b_pos < word_without_sign.len() - 1 {
                 let base_str = &word_without_sign[..b_pos];
@@ -698,30 +871,27 @@ impl Lexer {
                     (false, digits_str)
                 };
 
-                // Reject fractional base numbers (treat as symbol)
-                if !base_digits.contains('.') {
-                    if let Ok(base) = base_str.parse::<i32>() {
-                        if base >= 2 && base <= 62 {
-                            // rug's parse_radix only supports bases 2-36
-                            // For bases 37-62, we need custom parsing
-                            let value = if base <= 36 {
-                                Float::parse_radix(base_digits, base)
-                                    .ok()
-                                    .map(|v| v.complete(128))
-                            } else {
-                                parse_base_37_to_62(base_digits, base)
-                            };
+                if let Ok(base) = base_str.parse::<i32>() {
+                    if base >= 2 && base <= 62 {
+                        // rug's parse_radix only supports bases 2-36
+                        // For bases 37-62, we need custom parsing
+                        let value = if base <= 36 {
+                            Float::parse_radix(base_digits, base)
+                                .ok()
+                                .map(|v| v.complete(precision))
+                        } else {
+                            parse_base_37_to_62(base_digits, base, precision)
+                        };
 
-                            if let Some(mut value) = value {
-                                if is_negative ^ base_negative {
-                                    // XOR: negate if exactly one minus sign
-                                    value = -value;
-                                }
-                                return Ok(Token::Number {
-                                    value,
-                                    base: base as u8,
-                                });
+                        if let Some(mut value) = value {
+                            if is_negative ^ base_negative {
+                                // XOR: negate if exactly one minus sign
+                                value = -value;
                             }
+                            return Ok(Token::Number {
+                                value,
+                                base: base as u8,
+                            });
                         }
                     }
                 }
@@ -731,11 +901,30 @@ impl Lexer {
         // Decimal: standard float (use original word to preserve the sign)
         if let Ok(value) = Float::parse(word) {
             return Ok(Token::Number {
-                value: value.complete(128),
+                value: value.complete(precision),
                 base: 10,
             });
         }
 
+        // Decimal with an SI prefix suffix: 4.7k, 33p, 10u
+        if let Some(prefix) = word.chars().last() {
+            let mantissa = &word[..word.len() - prefix.len_utf8()];
+            let digits = mantissa.trim_start_matches(['-', '+']);
+            if let Some(exponent) = si_prefix_exponent(prefix) {
+                if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                    && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+                {
+                    // Shift the decimal exponent in the text so the value is rounded once
+                    if let Ok(value) = Float::parse(format!("{}e{}", mantissa, exponent)) {
+                        return Ok(Token::Number {
+                            value: value.complete(precision),
+                            base: 10,
+                        });
+                    }
+                }
+            }
+        }
+
         Err(Error::SyntaxError(format!("not a number: {}", word)))
     }
 }
@@ -10,7 +10,7 @@
 //! - Multiple number bases (2-62)
 //! - String operations
 //! - Control flow (if/then/else, for/next, while/repeat)
-//! - Variables with three-level scoping
+//! - Global variables and lexically scoped local variable frames
 //! - Programs as first-class objects
 //!
 //! ## Quick Start
@@ -12,22 +12,28 @@ fn main() {
         let mut ctx = Context::new();
         let expression = args[1..].join(" ");
 
+        let before = program::before_line(&mut ctx);
         match program::execute(&expression, &mut ctx) {
             Ok(()) => {
                 // Show stack (bottom to top, index 1 at bottom)
-                if ctx.stack.len() == 1 {
-                    // Single item: display without prefix
-                    if let Some(obj) = ctx.stack.get(0) {
-                        println!("{}", obj.display(&ctx.config));
+                let lines: Vec<String> = match &before {
+                    Some(before) if ctx.config.verified => {
+                        program::verified_display(&expression, before, &ctx)
                     }
+                    _ => ctx
+                        .stack
+                        .iter()
+                        .map(|obj| obj.display(&ctx.config))
+                        .collect(),
+                };
+                if lines.len() == 1 {
+                    // Single item: display without prefix
+                    println!("{}", lines[0]);
                 } else {
                     // Multiple items: display from bottom (oldest) to top (newest)
                     // Index i corresponds to level (i+1), so higher levels print first
-                    for i in (0..ctx.stack.len()).rev() {
-                        if let Some(obj) = ctx.stack.get(i) {
-                            let stack_level = i + 1;
-                            println!("{}> {}", stack_level, obj.display(&ctx.config));
-                        }
+                    for (i, line) in lines.iter().enumerate().rev() {
+                        println!("{}> {}", i + 1, line);
                     }
                 }
             }
@@ -42,6 +48,9 @@ fn main() {
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
+                if let Some(context) = &ctx.error_context {
+                    eprintln!("{}", context);
+                }
                 std::process::exit(1);
             }
         }
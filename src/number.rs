@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-only
 // Number formatting and configuration
 
+use rug::float::Round;
 use rug::ops::Pow;
-use rug::Float;
+use rug::{Float, Integer};
+use std::cmp::Ordering;
 
 /// Display mode for numbers
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,6 +15,10 @@ pub enum DisplayMode {
     Fix,
     /// Scientific notation
     Sci,
+    /// Engineering notation (exponent is a multiple of 3)
+    Eng,
+    /// Engineering notation with SI prefixes (4.7k, 100n)
+    Si,
 }
 
 impl Default for DisplayMode {
@@ -21,6 +27,52 @@ impl Default for DisplayMode {
     }
 }
 
+/// Angle unit used by trigonometric functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleMode {
+    /// Radians
+    #[default]
+    Rad,
+    /// Degrees (360 per turn)
+    Deg,
+    /// Gradians (400 per turn)
+    Grad,
+}
+
+impl AngleMode {
+    /// Number of units in a full turn, None for radians
+    pub fn units_per_turn(&self) -> Option<u32> {
+        match self {
+            AngleMode::Rad => None,
+            AngleMode::Deg => Some(360),
+            AngleMode::Grad => Some(400),
+        }
+    }
+
+    /// Suffix marking an angle in this unit in polar complexes, empty for radians
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AngleMode::Rad => "",
+            AngleMode::Deg => "°",
+            AngleMode::Grad => "ᵍ",
+        }
+    }
+}
+
+/// Display format of complex numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComplexFormat {
+    /// Pair of real and imaginary parts, (3,4)
+    #[default]
+    Pair,
+    /// Rectangular with an i imaginary unit, 3+4i
+    RectI,
+    /// Rectangular with a j imaginary unit, 3+4j
+    RectJ,
+    /// Polar modulus and argument in the current angle unit, (5∠53.13°)
+    Polar,
+}
+
 /// Number formatting configuration
 #[derive(Debug, Clone)]
 pub struct NumberConfig {
@@ -32,6 +84,42 @@ pub struct NumberConfig {
 
     /// Number of decimal digits to display (default 38)
     pub digits: i32,
+
+    /// Word size in bits for rotates, byte swap and bit counts (default 64)
+    pub word_size: u32,
+
+    /// Angle unit of trigonometric functions (default radians)
+    pub angle_mode: AngleMode,
+
+    /// Promote real domain errors to complex results instead of nan (default true)
+    pub complex_results: bool,
+
+    /// Rounding direction of real operations (default to nearest)
+    pub rounding: Round,
+
+    /// Modulus of integer arithmetic, None when modular mode is off (default)
+    pub modulus: Option<Integer>,
+
+    /// Group digits by thousands in decimal and by 4 digits in other bases (default false)
+    pub grouping: bool,
+
+    /// Decimal separator of decimal numbers, '.' or ',' (default '.')
+    pub decimal_separator: char,
+
+    /// Verify displayed digits by re-running command lines at higher precision (default false)
+    pub verified: bool,
+
+    /// Display format of complex numbers (default (re,im) pairs)
+    pub complex_format: ComplexFormat,
+
+    /// Base of numbers entered without a 0x, 0b or Nb prefix (default 10)
+    pub input_base: u8,
+
+    /// Maximum number of programs being run, calling each other (default 10000)
+    pub max_depth: usize,
+
+    /// Print each object run with the stack depth and top values (default false)
+    pub trace: bool,
 }
 
 impl NumberConfig {
@@ -40,6 +128,18 @@ impl NumberConfig {
             mode: DisplayMode::Std,
             precision_bits: 128, // kMpfrDefaultPrecBits
             digits: 38,          // kDefaultDecimalDigits
+            word_size: 64,
+            angle_mode: AngleMode::Rad,
+            complex_results: true,
+            rounding: Round::Nearest,
+            modulus: None,
+            grouping: false,
+            decimal_separator: '.',
+            verified: false,
+            complex_format: ComplexFormat::Pair,
+            input_base: 10,
+            max_depth: DEFAULT_MAX_DEPTH,
+            trace: false,
         }
     }
 
@@ -55,8 +155,29 @@ impl Default for NumberConfig {
     }
 }
 
+/// Apply an in-place rug operation in a rounding direction, e.g. `Float::sin_round`
+pub fn rounded(
+    mut value: Float,
+    round: Round,
+    op: impl FnOnce(&mut Float, Round) -> Ordering,
+) -> Float {
+    op(&mut value, round);
+    value
+}
+
+/// Separator between groups of decimal digits (narrow no-break space, as in 1 234 567)
+pub const DECIMAL_GROUP_SEPARATOR: char = '\u{202f}';
+
+/// Separator between groups of digits in non-decimal bases, as in 0xdead_beef
+pub const BASE_GROUP_SEPARATOR: char = '_';
+
+/// Default maximum number of programs being run, calling each other
+pub const DEFAULT_MAX_DEPTH: usize = 10_000;
+
 /// Format a number for display
-pub fn format_number(value: &Float, base: u8, mode: DisplayMode, digits: i32) -> String {
+pub fn format_number(value: &Float, base: u8, config: &NumberConfig) -> String {
+    let digits = config.digits;
+    let frac_digits = digits.max(0) as usize;
     // Special values (inf, nan) are always displayed without base prefixes
     if value.is_nan() {
         return "nan".to_string();
@@ -72,7 +193,7 @@ pub fn format_number(value: &Float, base: u8, mode: DisplayMode, digits: i32) ->
     match base {
         10 => {
             // Decimal formatting
-            match mode {
+            let formatted = match config.mode {
                 DisplayMode::Std => {
                     // Standard mode: show significant digits, removing trailing zeros
                     format_std(value, digits as usize)
@@ -85,11 +206,20 @@ pub fn format_number(value: &Float, base: u8, mode: DisplayMode, digits: i32) ->
                     // Scientific notation with explicit +/- and zero-padded exponent
                     format_scientific(value, digits as usize)
                 }
-            }
+                DisplayMode::Eng => {
+                    // Engineering notation, same exponent format as scientific
+                    format_engineering(value, digits as usize, false)
+                }
+                DisplayMode::Si => {
+                    // Engineering notation with the exponent written as an SI prefix
+                    format_engineering(value, digits as usize, true)
+                }
+            };
+            localize_decimal(&formatted, config)
         }
         2 => {
             // Binary formatting: -0b... not 0b-...
-            let base_str = format_base(value, 2);
+            let base_str = group_base(format_base(value, 2, frac_digits), config);
             if base_str.starts_with('-') {
                 format!("-0b{}", &base_str[1..])
             } else {
@@ -98,7 +228,7 @@ pub fn format_number(value: &Float, base: u8, mode: DisplayMode, digits: i32) ->
         }
         16 => {
             // Hexadecimal formatting: -0x... not 0x-...
-            let base_str = format_base(value, 16);
+            let base_str = group_base(format_base(value, 16, frac_digits), config);
             if base_str.starts_with('-') {
                 format!("-0x{}", &base_str[1..])
             } else {
@@ -107,7 +237,7 @@ pub fn format_number(value: &Float, base: u8, mode: DisplayMode, digits: i32) ->
         }
         _ => {
             // Other bases (2-62): -Nb... not Nb-...
-            let base_str = format_base(value, base as i32);
+            let base_str = group_base(format_base(value, base as i32, frac_digits), config);
             if base_str.starts_with('-') {
                 format!("-{}b{}", base, &base_str[1..])
             } else {
@@ -117,6 +247,71 @@ pub fn format_number(value: &Float, base: u8, mode: DisplayMode, digits: i32) ->
     }
 }
 
+/// Apply the decimal separator and digit grouping to a formatted decimal number
+/// The exponent or SI prefix following the mantissa is left as is
+fn localize_decimal(formatted: &str, config: &NumberConfig) -> String {
+    if !config.grouping && config.decimal_separator == '.' {
+        return formatted.to_string();
+    }
+    let (sign, body) = match formatted.strip_prefix('-') {
+        Some(body) => ("-", body),
+        None => ("", formatted),
+    };
+    let end = body
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(body.len());
+    let (mantissa, rest) = body.split_at(end);
+    let mantissa = if config.grouping {
+        group_digits(mantissa, 3, DECIMAL_GROUP_SEPARATOR)
+    } else {
+        mantissa.to_string()
+    };
+    format!(
+        "{}{}{}",
+        sign,
+        mantissa.replace('.', &config.decimal_separator.to_string()),
+        rest
+    )
+}
+
+/// Group the digits of a non-decimal number by 4 when grouping is on
+fn group_base(formatted: String, config: &NumberConfig) -> String {
+    if !config.grouping {
+        return formatted;
+    }
+    match formatted.strip_prefix('-') {
+        Some(digits) => format!("-{}", group_digits(digits, 4, BASE_GROUP_SEPARATOR)),
+        None => group_digits(&formatted, 4, BASE_GROUP_SEPARATOR),
+    }
+}
+
+/// Insert a separator every `size` digits, from the point leftwards in the integer part
+/// and rightwards in the fractional part: 1234567.8912 -> 1 234 567.891 2
+fn group_digits(digits: &str, size: usize, separator: char) -> String {
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (digits, None),
+    };
+
+    let mut output = String::new();
+    for (i, ch) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % size == 0 {
+            output.push(separator);
+        }
+        output.push(ch);
+    }
+    if let Some(frac_part) = frac_part {
+        output.push('.');
+        for (i, ch) in frac_part.chars().enumerate() {
+            if i > 0 && i % size == 0 {
+                output.push(separator);
+            }
+            output.push(ch);
+        }
+    }
+    output
+}
+
 /// Format a Float in standard mode with intelligent precision
 /// Removes trailing zeros and unnecessary decimal points
 fn format_std(value: &Float, max_digits: usize) -> String {
@@ -231,9 +426,8 @@ fn scientific_to_decimal(s: &str, _max_digits: usize) -> String {
 }
 
 /// Format a Float in fixed-point notation with exactly N decimal places
+/// The digits are computed from the exact value, whatever its precision and magnitude
 fn format_fixed(value: &Float, decimal_places: usize) -> String {
-    use std::fmt::Write;
-
     // Handle special values
     if value.is_nan() {
         return "nan".to_string();
@@ -246,52 +440,28 @@ fn format_fixed(value: &Float, decimal_places: usize) -> String {
         };
     }
 
-    // Round the value to the specified number of decimal places
-    let scale_factor = Float::with_val(value.prec(), 10u32).pow(decimal_places as u32);
-
-    // Scale up, round, scale down
-    let scaled = Float::with_val(value.prec(), value * &scale_factor);
-    let rounded = Float::with_val(value.prec(), scaled.round());
-    let result_val = Float::with_val(value.prec(), rounded / &scale_factor);
+    // Scale the exact rational value by 10^N and round half away from zero
+    let scale = Integer::from(10u32).pow(decimal_places as u32);
+    let scaled = value.to_rational().unwrap_or_default() * scale;
+    let units = scaled.round().into_numer_denom().0.abs();
 
-    // Extract integer and fractional parts
-    let is_negative = result_val.is_sign_negative();
-    let abs_val = result_val.abs();
-    let integer_part = Float::with_val(value.prec(), abs_val.clone().floor());
-    let fractional_part = Float::with_val(value.prec(), abs_val - &integer_part);
-
-    // Get integer part as string (convert to Integer to avoid f64 precision loss)
-    let int_str = if let Some(int_val) = integer_part.to_integer() {
-        int_val.to_string()
-    } else {
-        // Fallback for very large numbers
-        format!("{}", integer_part.to_f64() as i64)
-    };
-
-    // Get fractional digits by multiplying by 10^decimal_places and rounding
-    let frac_scaled = Float::with_val(value.prec(), fractional_part * &scale_factor);
-    let frac_rounded = Float::with_val(value.prec(), frac_scaled.round());
-    let frac_int = if let Some(frac_val) = frac_rounded.to_integer() {
-        frac_val
-    } else {
-        // Fallback for very large numbers
-        use rug::Integer;
-        Integer::from(frac_rounded.to_f64() as u64)
-    };
+    // Left-pad so that there is at least one integer digit
+    let mut digits = units.to_string();
+    if digits.len() <= decimal_places {
+        digits.insert_str(0, &"0".repeat(decimal_places + 1 - digits.len()));
+    }
 
     // Assemble the result
     let mut output = String::new();
-    if is_negative {
+    if value.is_sign_negative() {
         output.push('-');
     }
-
-    if decimal_places == 0 {
+    let int_len = digits.len() - decimal_places;
+    output.push_str(&digits[..int_len]);
+    if decimal_places > 0 {
         // No decimal point for 0 decimal places
-        write!(&mut output, "{}", int_str).unwrap();
-    } else {
-        // Format fractional part with leading zeros if needed
-        let frac_str = format!("{:0width$}", frac_int, width = decimal_places);
-        write!(&mut output, "{}.{}", int_str, frac_str).unwrap();
+        output.push('.');
+        output.push_str(&digits[int_len..]);
     }
 
     output
@@ -312,36 +482,92 @@ fn format_scientific(value: &Float, decimal_places: usize) -> String {
         };
     }
     if value.is_zero() {
-        return format!("0.{}e+00", "0".repeat(decimal_places));
+        let zero = format!("0.{}", "0".repeat(decimal_places));
+        return format!("{}e+00", zero.trim_end_matches('.'));
     }
 
-    // Get sign
-    let is_negative = value.is_sign_negative();
-    let abs_val = value.clone().abs();
-
-    // Calculate exponent (base 10)
-    let log10 = abs_val.clone().log10();
-    let exponent = log10.to_f64().floor() as i32;
-
-    // Calculate mantissa: value / 10^exponent
-    let ten = Float::with_val(value.prec(), 10);
-    let scale = Float::with_val(value.prec(), ten.pow(exponent));
-    let mantissa = Float::with_val(value.prec(), abs_val / scale);
-
-    // Format mantissa with requested decimal places
-    let mantissa_str = format!("{:.prec$}", mantissa.to_f64(), prec = decimal_places);
+    // Correctly rounded decimal digits from MPFR: value = 0.d1d2d3... * 10^exp
+    let (is_negative, digits, exp) = value.to_sign_string_exp(10, Some(decimal_places + 1));
+    let exponent = exp.unwrap_or(0) - 1;
 
     // Build final string
+    let mut mantissa = digits[..1].to_string();
+    if decimal_places > 0 {
+        mantissa.push('.');
+        mantissa.push_str(&digits[1..]);
+    }
     format!(
         "{}{}e{:+03}",
         if is_negative { "-" } else { "" },
-        mantissa_str,
+        mantissa,
         exponent
     )
 }
 
+/// SI prefixes from 10^-30 to 10^30, by steps of 3
+const SI_PREFIXES: [&str; 21] = [
+    "q", "r", "y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y",
+    "R", "Q",
+];
+
+/// Return the power of ten of an SI prefix, `u` being accepted for micro
+pub fn si_prefix_exponent(prefix: char) -> Option<i32> {
+    if prefix == 'u' {
+        return Some(-6);
+    }
+    SI_PREFIXES
+        .iter()
+        .position(|p| p.chars().eq(std::iter::once(prefix)))
+        .map(|i| (i as i32 - 10) * 3)
+}
+
+/// Format a Float in engineering notation: 1 to 3 integer digits and an exponent multiple
+/// of 3, with `decimal_places` digits after the first significant one
+/// Example: 4.700e+03, or 4.700k when `si` is set
+fn format_engineering(value: &Float, decimal_places: usize, si: bool) -> String {
+    if value.is_zero() {
+        let zero = format!("0.{}", "0".repeat(decimal_places));
+        let zero = zero.trim_end_matches('.');
+        return if si {
+            zero.to_string()
+        } else {
+            format!("{}e+00", zero)
+        };
+    }
+
+    // Exact decimal digits from MPFR: value = 0.d1d2d3... * 10^exp
+    let (is_negative, mut digits, exp) = value.to_sign_string_exp(10, Some(decimal_places + 1));
+    let exponent = exp.unwrap_or(0) - 1;
+    let eng_exponent = exponent.div_euclid(3) * 3;
+    let int_digits = (exponent - eng_exponent + 1) as usize;
+    while digits.len() < int_digits {
+        digits.push('0');
+    }
+
+    let mut mantissa = digits[..int_digits].to_string();
+    if digits.len() > int_digits {
+        mantissa.push('.');
+        mantissa.push_str(&digits[int_digits..]);
+    }
+
+    let prefix = usize::try_from((eng_exponent + 30) / 3)
+        .ok()
+        .and_then(|i| SI_PREFIXES.get(i));
+    let suffix = match prefix {
+        Some(prefix) if si => prefix.to_string(),
+        _ => format!("e{:+03}", eng_exponent),
+    };
+    format!(
+        "{}{}{}",
+        if is_negative { "-" } else { "" },
+        mantissa,
+        suffix
+    )
+}
+
 /// Format number in arbitrary base (helper function)
-fn format_base(value: &Float, base: i32) -> String {
+/// The fractional part is rounded to at most `max_frac_digits` digits, trailing zeros removed
+fn format_base(value: &Float, base: i32, max_frac_digits: usize) -> String {
     // Handle special values
     if value.is_nan() {
         return "nan".to_string();
@@ -362,25 +588,34 @@ fn format_base(value: &Float, base: i32) -> String {
     }
 
     let is_negative = value.is_sign_negative();
-    let prec = value.prec();
-    let abs_val = value.clone().abs();
-
-    // Split into integer and fractional parts
-    let integer_part = Float::with_val(prec, abs_val.clone().floor());
-    let integer_val = match integer_part.to_integer() {
-        Some(int) => int,
-        None => return format!("{}", value),
-    };
 
-    // Integer part
-    let mut result = if base <= 36 {
-        integer_val.to_string_radix(base)
+    // Don't show more fractional digits than the mantissa holds: all of them in power of 2
+    // bases where they are exact, only the complete ones in other bases
+    let frac_bits = (value.prec() as i64 - value.get_exp().unwrap_or(0) as i64).max(0) as f64;
+    let digit_bits = (base as f64).log2();
+    let frac_digits = if (base as u32).is_power_of_two() {
+        (frac_bits / digit_bits).ceil() as usize
     } else {
-        format_base_37_to_62(&integer_val, base)
+        (frac_bits / digit_bits).floor() as usize
     };
+    let frac_digits = frac_digits.min(max_frac_digits);
+
+    // Scale the exact value by base^frac_digits and round, so that carries reach the integer part
+    let scale = Integer::from(base).pow(frac_digits as u32);
+    let scaled = value.to_rational().unwrap_or_default().abs() * &scale;
+    let units = scaled.round().into_numer_denom().0;
+    let (integer_val, frac_val) = units.div_rem(scale);
 
-    // No fractional part display for non-decimal bases
-    // The integer part is already extracted above
+    // Integer part
+    let mut result = format_integer_base(&integer_val, base);
+
+    // Fractional part, left-padded with zeros to frac_digits
+    if frac_val != 0 {
+        let frac_str = format_integer_base(&frac_val, base);
+        let frac_str = format!("{}{}", "0".repeat(frac_digits - frac_str.len()), frac_str);
+        result.push('.');
+        result.push_str(frac_str.trim_end_matches('0'));
+    }
 
     if is_negative {
         result.insert(0, '-');
@@ -389,6 +624,80 @@ fn format_base(value: &Float, base: i32) -> String {
     result
 }
 
+/// Format a special value (nan, inf, -inf), None for finite values
+fn format_special(value: &Float) -> Option<String> {
+    if value.is_nan() {
+        Some("nan".to_string())
+    } else if value.is_infinite() {
+        Some(
+            if value.is_sign_negative() {
+                "-inf"
+            } else {
+                "inf"
+            }
+            .to_string(),
+        )
+    } else {
+        None
+    }
+}
+
+/// Exact decimal expansion of a binary float, ex: 0.1 at 53 bits is
+/// 0.1000000000000000055511151231257827021181583404541015625
+pub fn format_exact_decimal(value: &Float) -> String {
+    if let Some(special) = format_special(value) {
+        return special;
+    }
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+
+    // value = num / 2^k = num * 5^k / 10^k
+    let (num, den) = value.to_rational().unwrap().into_numer_denom();
+    let k = den.significant_bits() - 1;
+    let digits = (num.abs() * Integer::from(5).pow(k)).to_string();
+    let k = k as usize;
+    if k == 0 {
+        return format!("{}{}", sign, digits);
+    }
+    let digits = "0".repeat((k + 1).saturating_sub(digits.len())) + &digits;
+    let (int_part, frac_part) = digits.split_at(digits.len() - k);
+    format!("{}{}.{}", sign, int_part, frac_part)
+}
+
+/// Binary mantissa and power of 2 exponent of a float, ex: 0b1.1p-1 for 0.75
+pub fn format_binary_exponent(value: &Float) -> String {
+    if let Some(special) = format_special(value) {
+        return special;
+    }
+    if value.is_zero() {
+        return if value.is_sign_negative() {
+            "-0b0"
+        } else {
+            "0b0"
+        }
+        .to_string();
+    }
+
+    // value = 0.1xxx * 2^exp = 1.xxx * 2^(exp-1)
+    let (negative, digits, exp) = value.to_sign_string_exp(2, None);
+    let fraction = digits[1..].trim_end_matches('0');
+    format!(
+        "{}0b1{}{}p{}",
+        if negative { "-" } else { "" },
+        if fraction.is_empty() { "" } else { "." },
+        fraction,
+        exp.unwrap_or(0) - 1
+    )
+}
+
+/// Format a non-negative integer in bases 2-62
+fn format_integer_base(value: &Integer, base: i32) -> String {
+    if base <= 36 {
+        value.to_string_radix(base)
+    } else {
+        format_base_37_to_62(value, base)
+    }
+}
+
 /// Format an integer in bases 37-62
 /// Digit mapping: 0-9 (0-9), A-Z (10-35), a-z (36-61)
 fn format_base_37_to_62(value: &rug::Integer, base: i32) -> String {
@@ -3,30 +3,34 @@
 
 use crate::context::Context;
 use crate::error::Result;
-use crate::number::{format_number, NumberConfig};
+use crate::lexer::Span;
+use crate::number::{format_number, ComplexFormat, NumberConfig};
+use crate::operations::trig::atan2_angle;
 use rug::{Complex, Float};
 
 #[derive(Debug, Clone)]
 pub struct Program {
     pub objects: Vec<Object>,
+    pub spans: Vec<Span>, // Position of each object in value, for error reports
     pub value: String,
     pub local_vars: Vec<String>, // Local variable names from -> declaration
     pub prefix_objects: Vec<Object>, // Code to execute before binding local vars
+    pub prefix_spans: Vec<Span>, // Position of each prefix object in value
     pub auto_eval: bool,         // If true, auto-evaluate when encountered in run_objects
 }
 
 /// Branch arguments for control flow operations
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct BranchArgs {
     pub arg1: usize,
     pub arg2: usize,
     pub arg3: usize,
-    pub first_index: f64, // Start value for loops (never modified after init)
-    pub last_index: f64,  // End value for loops
+    pub first_index: Float, // Start value for loops (never modified after init)
+    pub last_index: Float,  // End value for loops
     pub arg_bool: bool,
     pub condition: usize,       // For IF: stores condition result (0 or 1)
-    pub iteration_count: usize, // For STEP: tracks iteration number to avoid accumulation errors
-    pub step_value: f64,        // For STEP: stores the step increment
+    pub iteration_count: usize, // For NEXT/STEP: tracks iteration number to avoid accumulation errors
+    pub step_value: Float,      // For NEXT/STEP: stores the step increment
 }
 
 impl Default for BranchArgs {
@@ -35,12 +39,12 @@ impl Default for BranchArgs {
             arg1: usize::MAX,
             arg2: usize::MAX,
             arg3: usize::MAX,
-            first_index: 0.0,
-            last_index: 0.0,
+            first_index: Float::new(53),
+            last_index: Float::new(53),
             arg_bool: false,
             condition: 0,
             iteration_count: 0,
-            step_value: 1.0,
+            step_value: Float::with_val(53, 1),
         }
     }
 }
@@ -105,18 +109,51 @@ impl Object {
     /// Display this object as a string
     pub fn display(&self, config: &NumberConfig) -> String {
         match self {
-            Object::Number { value, base } => {
-                format_number(value, *base, config.mode, config.digits)
-            }
+            Object::Number { value, base } => format_number(value, *base, config),
             Object::Complex {
                 value,
                 re_base,
                 im_base,
-            } => {
-                let re_str = format_number(value.real(), *re_base, config.mode, config.digits);
-                let im_str = format_number(value.imag(), *im_base, config.mode, config.digits);
-                format!("({},{})", re_str, im_str)
-            }
+            } => match config.complex_format {
+                ComplexFormat::Pair => {
+                    let re_str = format_number(value.real(), *re_base, config);
+                    let im_str = format_number(value.imag(), *im_base, config);
+                    // Keep the parts apart when the decimal separator is a comma
+                    if config.decimal_separator == ',' {
+                        format!("({};{})", re_str, im_str)
+                    } else {
+                        format!("({},{})", re_str, im_str)
+                    }
+                }
+                ComplexFormat::RectI | ComplexFormat::RectJ => {
+                    let (re, im) = (value.real(), value.imag());
+                    let sign = if im.is_sign_negative() { '-' } else { '+' };
+                    let unit = if config.complex_format == ComplexFormat::RectI {
+                        'i'
+                    } else {
+                        'j'
+                    };
+                    let im_abs = Float::with_val(im.prec(), im.abs_ref());
+                    format!(
+                        "{}{}{}{}",
+                        format_number(re, *re_base, config),
+                        sign,
+                        format_number(&im_abs, *im_base, config),
+                        unit
+                    )
+                }
+                ComplexFormat::Polar => {
+                    let (re, im) = (value.real(), value.imag());
+                    let modulus = Float::with_val(re.prec(), value.abs_ref());
+                    let angle = atan2_angle(im.clone(), re, config.angle_mode, config.rounding);
+                    format!(
+                        "({}∠{}{})",
+                        format_number(&modulus, *re_base, config),
+                        format_number(&angle, 10, config),
+                        config.angle_mode.symbol()
+                    )
+                }
+            },
             Object::Symbol { name, auto_eval } => {
                 if *auto_eval {
                     name.clone()
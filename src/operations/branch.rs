@@ -4,6 +4,7 @@
 use crate::context::Context;
 use crate::error::{Error, Result};
 use crate::object::{BranchArgs, Object};
+use rug::Float;
 
 // Special return values for branch operations
 pub const STEP_OUT: usize = usize::MAX; // Continue to next instruction
@@ -18,12 +19,32 @@ pub fn rpnx_if(_ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
     Ok(STEP_OUT)
 }
 
+/// IFERR: iferr (mark start of an error trap clause)
+/// Stack: (unchanged)
+/// arg1 = address of THEN+1 (error clause), jumped to by the execution loop on error
+pub fn rpnx_iferr(_ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
+    Ok(STEP_OUT)
+}
+
+/// CASE: case (mark start of a multi-way branch)
+/// Stack: (unchanged)
+/// Each `test then body end` clause is tried in turn, the clause END jumping after the CASE END
+pub fn rpnx_case(_ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
+    Ok(STEP_OUT)
+}
+
 /// THEN: then (evaluate condition and branch)
 /// Stack: condition -> (empty)
 /// arg1 = address of THEN+1 (true branch)
 /// arg2 = address of ELSE+1 or END (false branch)
 /// arg3 = address of IF
+/// arg_bool = THEN of IFERR: reached without error, skip the error clause
 pub fn rpnx_then(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
+    if args.arg_bool {
+        args.condition = 0;
+        return Ok(args.arg2);
+    }
+
     min_arguments!(ctx, 1);
     arg_must_be!(ctx, 0, Number);
 
@@ -50,10 +71,11 @@ pub fn rpnx_else(_ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     }
 }
 
-/// END: end (terminate IF/THEN/ELSE, DO/UNTIL, or WHILE/REPEAT)
+/// END: end (terminate IF/THEN/ELSE, CASE, DO/UNTIL, or WHILE/REPEAT)
 /// For IF context: just continue
 /// For DO/UNTIL: arg1 = DO+1 (loop restart)
 /// For WHILE/REPEAT: arg2 = WHILE+1 (test restart)
+/// For CASE clause: arg2 = CASE END+1 (leave the CASE)
 pub fn rpnx_end(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     // Check if this is a DO..UNTIL context
     if args.arg1 != STEP_OUT {
@@ -73,10 +95,18 @@ pub fn rpnx_end(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     Ok(STEP_OUT)
 }
 
+/// Value of the loop counter after `iteration_count` steps from `first_index`
+/// Computed from the start value rather than accumulated, so that no rounding error builds up
+pub fn loop_value(args: &BranchArgs, precision: u32) -> Float {
+    let offset = Float::with_val(precision, &args.step_value * args.iteration_count);
+    Float::with_val(precision, &args.first_index + offset)
+}
+
 /// FOR: for (start counted loop with variable)
 /// Stack: <start> <end> -> (empty)
 /// arg1 = address of loop variable symbol
 /// arg2 = address of NEXT/STEP
+/// arg_bool = loop ended by STEP: run at least once, STEP deciding the direction
 /// Stores loop bounds in first_index/last_index
 pub fn rpnx_for(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     min_arguments!(ctx, 2);
@@ -86,10 +116,11 @@ pub fn rpnx_for(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     let end_value = pop_one_number!(ctx);
     let start_value = pop_one_number!(ctx);
 
-    args.first_index = start_value.to_f64();
-    args.last_index = end_value.to_f64();
+    let skip = start_value > end_value && !args.arg_bool;
+    args.first_index = start_value;
+    args.last_index = end_value;
 
-    if start_value > end_value {
+    if skip {
         // Skip loop entirely
         Ok(args.arg2 + 1)
     } else {
@@ -103,22 +134,19 @@ pub fn rpnx_for(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
 /// arg1 = address of FOR/START
 /// Note: This function needs access to the FOR/START instruction to get its arg1
 /// The actual jump target is calculated in the execution loop
-pub fn rpnx_next(_ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
+pub fn rpnx_next(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     // Initialize counter on first iteration
     if !args.arg_bool {
         args.arg_bool = true;
         args.iteration_count = 0;
-        args.step_value = 1.0;
+        args.step_value = Float::with_val(ctx.config.precision_bits, 1);
     }
 
     // Increment iteration counter
     args.iteration_count += 1;
 
-    // Compute current value: start + (iteration * 1)
-    let current_value = args.first_index + (args.iteration_count as f64 * 1.0);
-
     // Test condition
-    if current_value > args.last_index {
+    if loop_value(args, ctx.config.precision_bits) > args.last_index {
         args.arg_bool = false; // Reset for next loop
         args.iteration_count = 0;
         Ok(STEP_OUT) // Exit loop
@@ -132,64 +160,38 @@ pub fn rpnx_next(_ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
 /// STEP: step (custom step increment for loop)
 /// Stack: <step> -> (empty)
 /// arg1 = address of FOR/START
+/// A negative step counts down to the end value
 pub fn rpnx_step(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     min_arguments!(ctx, 1);
     arg_must_be!(ctx, 0, Number);
 
-    let (step_value, _base) = match ctx.stack.pop().unwrap() {
-        Object::Number { value, base } => (value, base),
-        _ => unreachable!(),
-    };
-
-    let step_f64 = step_value.to_f64();
+    let step_value = pop_one_number!(ctx);
 
-    if step_f64 <= 0.0 {
+    if step_value.is_zero() || step_value.is_nan() {
         return Err(Error::OutOfRange);
     }
 
     // Initialize on first iteration
     if !args.arg_bool {
         args.arg_bool = true;
-        args.step_value = step_f64;
+        args.step_value = step_value;
         args.iteration_count = 0;
-
-        // Store the exact step value in heap for precise computation
-        let step_key = format!("__step_{}", args.arg1);
-        ctx.local_heap.store(
-            step_key,
-            Object::Number {
-                value: step_value,
-                base: 10,
-            },
-        );
     }
 
     // Increment iteration counter
     args.iteration_count += 1;
 
-    // Retrieve exact step value from heap and compute with MPFR precision
-    let step_key = format!("__step_{}", args.arg1);
-    let current_value_f64 = if let Some(Object::Number {
-        value: step_mpfr, ..
-    }) = ctx.local_heap.recall(&step_key)
-    {
-        use rug::Float;
-        // Compute: start + (iteration * step) using MPFR
-        let start = Float::with_val(ctx.config.precision_bits, args.first_index);
-        let iteration = Float::with_val(ctx.config.precision_bits, args.iteration_count);
-        let current = start + (iteration * step_mpfr);
-        current.to_f64()
+    // Test condition, in the direction of the step
+    let current_value = loop_value(args, ctx.config.precision_bits);
+    let done = if args.step_value.is_sign_positive() {
+        current_value > args.last_index
     } else {
-        // Fallback to f64 if step not found (shouldn't happen)
-        args.first_index + (args.iteration_count as f64 * args.step_value)
+        current_value < args.last_index
     };
 
-    // Test condition
-    if current_value_f64 > args.last_index {
+    if done {
         args.arg_bool = false;
         args.iteration_count = 0;
-        // Clean up step value from heap
-        ctx.local_heap.purge(&step_key);
         Ok(STEP_OUT)
     } else {
         // Return FOR/START index (execution loop will compute jump target)
@@ -201,6 +203,7 @@ pub fn rpnx_step(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
 /// Stack: <start> <end> -> (empty)
 /// arg1 = STEP_OUT (no loop variable)
 /// arg2 = address of NEXT/STEP
+/// arg_bool = loop ended by STEP: run at least once, STEP deciding the direction
 pub fn rpnx_start(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     min_arguments!(ctx, 2);
     arg_must_be!(ctx, 0, Number);
@@ -209,10 +212,11 @@ pub fn rpnx_start(ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
     let end_value = pop_one_number!(ctx);
     let start_value = pop_one_number!(ctx);
 
-    args.first_index = start_value.to_f64();
-    args.last_index = end_value.to_f64();
+    let skip = start_value > end_value && !args.arg_bool;
+    args.first_index = start_value;
+    args.last_index = end_value;
 
-    if start_value > end_value {
+    if skip {
         // Skip loop entirely
         Ok(args.arg2 + 1)
     } else {
@@ -254,12 +258,49 @@ pub fn rpnx_until(_ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
     Ok(STEP_OUT)
 }
 
+/// BREAK: break (leave the innermost loop)
+/// arg1 = address after the loop NEXT/STEP/END
+/// arg2 = address of the loop FOR/START/WHILE/DO
+/// arg3 = address of the loop NEXT/STEP/END
+pub fn rpnx_break(_ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
+    Ok(args.arg1)
+}
+
+/// CONTINUE: continue (go on with the next iteration of the innermost loop)
+/// arg1 = address of the loop NEXT, of WHILE+1 or of UNTIL
+/// arg2 = address of the loop FOR/START/WHILE/DO
+/// arg3 = address of the loop NEXT/END
+pub fn rpnx_continue(_ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
+    Ok(args.arg1)
+}
+
+/// RETURN: return (leave the program)
+/// arg1 = address after the last instruction of the program
+pub fn rpnx_return(_ctx: &mut Context, args: &mut BranchArgs) -> Result<usize> {
+    Ok(args.arg1)
+}
+
 /// IFT: ift (inline if-then: condition value -> value if true, nothing if false)
 /// Stack: condition value -> value (if condition != 0) or nothing (if condition == 0)
-/// IFT: ift (inline if-then: condition value -> conditionally pushes value)
-/// Stack: condition value -> value (if true) or nothing (if false)
 /// If value is a Program, executes it; otherwise pushes it to stack
 pub fn rpnx_ift(ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
+    if let Some(value) = ift_value(ctx)? {
+        run_value(ctx, value)?;
+    }
+    Ok(STEP_OUT)
+}
+
+/// IFTE: ifte (inline if-then-else: condition true_value false_value -> pushes one value)
+/// Stack: condition true_value false_value -> result
+/// If value is a Program, executes it; otherwise pushes it to stack
+pub fn rpnx_ifte(ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
+    let value = ifte_value(ctx)?;
+    run_value(ctx, value)?;
+    Ok(STEP_OUT)
+}
+
+/// Pop the arguments of IFT, returning the value if the condition is true
+pub fn ift_value(ctx: &mut Context) -> Result<Option<Object>> {
     min_arguments!(ctx, 2);
     arg_must_be!(ctx, 1, Number);
 
@@ -269,64 +310,35 @@ pub fn rpnx_ift(ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
     // Pop condition
     let condition = pop_one_number!(ctx);
 
-    if condition != 0.0 {
-        // True: push value or execute program
-        match value {
-            Object::Program(p) => {
-                crate::program::run_objects(&p.objects, ctx)?;
-            }
-            _ => {
-                ctx.stack.push(value);
-            }
-        }
-    }
-    // else: drop the value (don't push it back)
-
-    Ok(STEP_OUT)
+    // False: drop the value (don't push it back)
+    Ok((condition != 0.0).then_some(value))
 }
 
-/// IFTE: ifte (inline if-then-else: condition true_value false_value -> pushes one value)
-/// Stack: condition true_value false_value -> result
-/// If value is a Program, executes it; otherwise pushes it to stack
-pub fn rpnx_ifte(ctx: &mut Context, _args: &mut BranchArgs) -> Result<usize> {
+/// Pop the arguments of IFTE, returning the value chosen by the condition
+pub fn ifte_value(ctx: &mut Context) -> Result<Object> {
     min_arguments!(ctx, 3);
     arg_must_be!(ctx, 2, Number);
 
-    let false_value = match ctx.stack.pop() {
-        Some(obj) => obj,
-        None => return Err(Error::MissingOperand),
-    };
-
-    let true_value = match ctx.stack.pop() {
-        Some(obj) => obj,
-        None => return Err(Error::MissingOperand),
-    };
-
+    let false_value = ctx.stack.pop().unwrap();
+    let true_value = ctx.stack.pop().unwrap();
     let condition = pop_one_number!(ctx);
 
     if condition != 0.0 {
-        // True branch
-        match true_value {
-            Object::Program(p) => {
-                crate::program::run_objects(&p.objects, ctx)?;
-            }
-            _ => {
-                ctx.stack.push(true_value);
-            }
-        }
+        Ok(true_value)
     } else {
-        // False branch
-        match false_value {
-            Object::Program(p) => {
-                crate::program::run_objects(&p.objects, ctx)?;
-            }
-            _ => {
-                ctx.stack.push(false_value);
-            }
-        }
+        Ok(false_value)
     }
+}
 
-    Ok(STEP_OUT)
+/// Execute a program, push any other value
+fn run_value(ctx: &mut Context, value: Object) -> Result<()> {
+    match value {
+        Object::Program(p) => crate::program::run_program(p, ctx),
+        _ => {
+            ctx.stack.push(value);
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -407,4 +419,29 @@ mod tests {
         assert_eq!(result, 6); // Should jump to arg1 + 1
         assert_eq!(ctx.stack.len(), 0);
     }
+
+    #[test]
+    fn test_step_downward() {
+        let mut ctx = Context::new();
+
+        // Set up loop: 10 1 ... -3 step
+        let mut args = BranchArgs {
+            arg1: 5, // Dummy FOR address
+            first_index: Float::with_val(128, 10),
+            last_index: Float::with_val(128, 1),
+            ..BranchArgs::default()
+        };
+
+        let mut values = vec![];
+        loop {
+            push_number!(ctx, Float::with_val(128, -3));
+            if rpnx_step(&mut ctx, &mut args).unwrap() == STEP_OUT {
+                break;
+            }
+            values.push(loop_value(&args, 128).to_f64());
+        }
+
+        assert_eq!(values, vec![7.0, 4.0, 1.0]);
+        assert!(!args.arg_bool); // Reset for next loop
+    }
 }
@@ -4,9 +4,34 @@
 use crate::context::Context;
 use crate::error::{Error, Result};
 use crate::object::Object;
-use rug::float::Constant;
+use crate::operations::trig::{atan2_angle, cos_angle, half_turn, sin_angle};
+use rug::float::Special;
 use rug::{Complex, Float};
 
+/// Push f(x) for a real x outside the real domain of f: the complex result when complex
+/// results are enabled, nan otherwise
+pub(crate) fn push_out_of_domain(
+    ctx: &mut Context,
+    value: Float,
+    base: u8,
+    f: impl FnOnce(Complex) -> Complex,
+) {
+    if ctx.config.complex_results {
+        let z = Complex::with_val(ctx.config.precision_bits, (value, 0));
+        ctx.stack.push(Object::Complex {
+            value: f(z),
+            re_base: base,
+            im_base: base,
+        });
+    } else {
+        push_number!(
+            ctx,
+            Float::with_val(ctx.config.precision_bits, Special::Nan),
+            base
+        );
+    }
+}
+
 /// RE: re (extract real part of complex number)
 /// Stack: complex -> real
 pub fn re(ctx: &mut Context) -> Result<()> {
@@ -47,7 +72,7 @@ pub fn im(ctx: &mut Context) -> Result<()> {
     }
 }
 
-/// ARG: arg (get argument/phase of complex number in radians)
+/// ARG: arg (get argument/phase of complex number in the current angle mode)
 /// Stack: complex -> angle
 pub fn arg(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
@@ -59,17 +84,21 @@ pub fn arg(ctx: &mut Context) -> Result<()> {
             let im = value.imag();
 
             // Compute atan2(im, re) using the context's current precision
-            let angle = Float::with_val(ctx.config.precision_bits, im)
-                .atan2(&Float::with_val(ctx.config.precision_bits, re));
+            let angle = atan2_angle(
+                Float::with_val(ctx.config.precision_bits, im),
+                &Float::with_val(ctx.config.precision_bits, re),
+                ctx.config.angle_mode,
+                ctx.config.rounding,
+            );
             push_number!(ctx, angle, re_base);
             Ok(())
         }
         Some(Object::Number { value, base }) => {
-            // Real number: arg is 0 if positive, pi if negative
+            // Real number: arg is 0 if positive, half a turn if negative
             let angle = if value >= 0 {
                 Float::with_val(ctx.config.precision_bits, 0)
             } else {
-                Float::with_val(ctx.config.precision_bits, Constant::Pi)
+                half_turn(ctx.config.precision_bits, ctx.config.angle_mode)
             };
             push_number!(ctx, angle, base);
             Ok(())
@@ -157,7 +186,7 @@ pub fn r_to_c(ctx: &mut Context) -> Result<()> {
 }
 
 /// P->R: p->r (convert polar to rectangular: magnitude angle -> complex)
-/// Stack: magnitude angle_radians -> complex
+/// Stack: magnitude angle -> complex, angle in the current angle mode
 pub fn p_to_r(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 2);
     arg_must_be!(ctx, 0, Number);
@@ -174,11 +203,11 @@ pub fn p_to_r(ctx: &mut Context) -> Result<()> {
     };
 
     // Convert polar to rectangular: x = r*cos(θ), y = r*sin(θ)
-    let cos_angle = angle.clone().cos();
-    let sin_angle = angle.sin();
+    let cos_a = cos_angle(angle.clone(), ctx.config.angle_mode, ctx.config.rounding);
+    let sin_a = sin_angle(angle, ctx.config.angle_mode, ctx.config.rounding);
 
-    let real = magnitude.clone() * cos_angle;
-    let imag = magnitude * sin_angle;
+    let real = magnitude.clone() * cos_a;
+    let imag = magnitude * sin_a;
 
     let complex_value = Complex::with_val(ctx.config.precision_bits, (real, imag));
 
@@ -192,7 +221,7 @@ pub fn p_to_r(ctx: &mut Context) -> Result<()> {
 }
 
 /// R->P: r->p (convert rectangular to polar: complex -> (magnitude, angle))
-/// Stack: complex -> complex(magnitude, angle_radians)
+/// Stack: complex -> complex(magnitude, angle), angle in the current angle mode
 /// Returns a complex number where the real part is the magnitude and imaginary part is the angle
 pub fn r_to_p(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
@@ -212,8 +241,12 @@ pub fn r_to_p(ctx: &mut Context) -> Result<()> {
             let im_squared = Float::with_val(ctx.config.precision_bits, im).square();
             let magnitude = (re_squared + im_squared).sqrt();
 
-            let angle = Float::with_val(ctx.config.precision_bits, im)
-                .atan2(&Float::with_val(ctx.config.precision_bits, re));
+            let angle = atan2_angle(
+                Float::with_val(ctx.config.precision_bits, im),
+                &Float::with_val(ctx.config.precision_bits, re),
+                ctx.config.angle_mode,
+                ctx.config.rounding,
+            );
 
             // Return as complex (magnitude, angle)
             let polar = Complex::with_val(ctx.config.precision_bits, (magnitude, angle));
@@ -225,12 +258,12 @@ pub fn r_to_p(ctx: &mut Context) -> Result<()> {
             Ok(())
         }
         Some(Object::Number { value, base }) => {
-            // Real number: magnitude is abs(value), angle is 0 or pi
+            // Real number: magnitude is abs(value), angle is 0 or half a turn
             let magnitude = Float::with_val(ctx.config.precision_bits, value.abs_ref());
             let angle = if value >= 0 {
                 Float::with_val(ctx.config.precision_bits, 0)
             } else {
-                Float::with_val(ctx.config.precision_bits, Constant::Pi)
+                half_turn(ctx.config.precision_bits, ctx.config.angle_mode)
             };
 
             // Return as complex (magnitude, angle)
@@ -350,4 +383,28 @@ mod tests {
             _ => panic!("Expected number"),
         }
     }
+
+    #[test]
+    fn test_out_of_domain() {
+        let mut ctx = Context::new();
+
+        // Complex results: sqrt(-4) = 2i
+        push_out_of_domain(&mut ctx, Float::with_val(128, -4), 10, |z| z.sqrt());
+        match ctx.stack.get(0).unwrap() {
+            Object::Complex { value, .. } => {
+                assert_eq!(value.real().to_f64(), 0.0);
+                assert_eq!(value.imag().to_f64(), 2.0);
+            }
+            _ => panic!("Expected complex number"),
+        }
+        ctx.stack.pop();
+
+        // Real results: nan
+        ctx.config.complex_results = false;
+        push_out_of_domain(&mut ctx, Float::with_val(128, -4), 10, |z| z.sqrt());
+        match ctx.stack.get(0).unwrap() {
+            Object::Number { value, .. } => assert!(value.is_nan()),
+            _ => panic!("Expected number"),
+        }
+    }
 }
@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: GPL-3.0-only
+// Encoding operations: IEEE-754 bit patterns and Qm.n fixed point
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use rug::{Float, Integer};
+
+/// IEEE-754 binary interchange format
+#[derive(Debug, Clone, Copy)]
+struct IeeeFormat {
+    /// Width of the exponent field in bits
+    exp_bits: u32,
+    /// Width of the trailing significand (fraction) field in bits
+    frac_bits: u32,
+}
+
+const BINARY16: IeeeFormat = IeeeFormat {
+    exp_bits: 5,
+    frac_bits: 10,
+};
+const BINARY32: IeeeFormat = IeeeFormat {
+    exp_bits: 8,
+    frac_bits: 23,
+};
+const BINARY64: IeeeFormat = IeeeFormat {
+    exp_bits: 11,
+    frac_bits: 52,
+};
+const BINARY128: IeeeFormat = IeeeFormat {
+    exp_bits: 15,
+    frac_bits: 112,
+};
+
+impl IeeeFormat {
+    /// Get the format for a width in bits (16, 32, 64 or 128)
+    fn from_width(width: u32) -> Option<Self> {
+        match width {
+            16 => Some(BINARY16),
+            32 => Some(BINARY32),
+            64 => Some(BINARY64),
+            128 => Some(BINARY128),
+            _ => None,
+        }
+    }
+
+    fn width(&self) -> u32 {
+        1 + self.exp_bits + self.frac_bits
+    }
+
+    fn bias(&self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    /// Smallest unbiased exponent of a normal number
+    fn emin(&self) -> i32 {
+        1 - self.bias()
+    }
+
+    /// Biased exponent field of infinities and nans
+    fn exp_max(&self) -> u32 {
+        (1 << self.exp_bits) - 1
+    }
+
+    /// Encode a value into its bit pattern, rounding to nearest even
+    fn encode(&self, value: &Float) -> Integer {
+        let sign = Integer::from(value.is_sign_negative() as u32) << (self.width() - 1);
+
+        if value.is_nan() {
+            // Quiet nan: exponent all ones, most significant fraction bit set
+            let exp = Integer::from(self.exp_max()) << self.frac_bits;
+            let quiet = Integer::from(1) << (self.frac_bits - 1);
+            return exp | quiet;
+        }
+        if value.is_infinite() {
+            return sign | (Integer::from(self.exp_max()) << self.frac_bits);
+        }
+        if value.is_zero() {
+            return sign;
+        }
+
+        let abs_val = value.clone().abs();
+
+        // Round to the format precision, the exponent may change after rounding
+        let rounded = Float::with_val(self.frac_bits + 1, &abs_val);
+        let exponent = rounded.get_exp().unwrap() - 1;
+
+        if exponent < self.emin() {
+            // Subnormal: count quanta of 2^(emin - frac_bits), a carry gives the smallest normal
+            let scaled = abs_val << (self.frac_bits as i32 - self.emin());
+            let quanta = scaled.round_even().to_integer().unwrap();
+            return sign | quanta;
+        }
+
+        if exponent > self.bias() {
+            // Overflow rounds to infinity
+            return sign | (Integer::from(self.exp_max()) << self.frac_bits);
+        }
+
+        let significand = (rounded << (self.frac_bits as i32 - exponent))
+            .to_integer()
+            .unwrap();
+        let fraction = significand.keep_bits(self.frac_bits);
+        let biased = Integer::from(exponent + self.bias()) << self.frac_bits;
+        sign | biased | fraction
+    }
+
+    /// Split a bit pattern into its sign, biased exponent and fraction fields
+    fn fields(&self, bits: &Integer) -> (u32, u32, Integer) {
+        let sign = bits.get_bit(self.width() - 1) as u32;
+        let exp = Integer::from(bits >> self.frac_bits)
+            .keep_bits(self.exp_bits)
+            .to_u32()
+            .unwrap();
+        let fraction = bits.clone().keep_bits(self.frac_bits);
+        (sign, exp, fraction)
+    }
+
+    /// Decode a bit pattern into a value
+    fn decode(&self, bits: &Integer, precision: u32) -> Float {
+        let (sign, exp, fraction) = self.fields(bits);
+        let precision = precision.max(self.frac_bits + 1);
+
+        let magnitude = if exp == self.exp_max() {
+            if fraction == 0 {
+                Float::with_val(precision, rug::float::Special::Infinity)
+            } else {
+                Float::with_val(precision, rug::float::Special::Nan)
+            }
+        } else if exp == 0 {
+            // Zero or subnormal
+            Float::with_val(precision, &fraction) << (self.emin() - self.frac_bits as i32)
+        } else {
+            let significand = (Integer::from(1) << self.frac_bits) | fraction;
+            let exponent = exp as i32 - self.bias();
+            Float::with_val(precision, &significand) << (exponent - self.frac_bits as i32)
+        };
+
+        if sign == 1 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+/// Pop a non-negative integer that fits on `width` bits
+fn pop_bit_pattern(ctx: &mut Context, width: u32) -> Result<Integer> {
+    let value = pop_one_number!(ctx);
+    let bits = match value.to_integer() {
+        Some(bits) if value.is_integer() => bits,
+        _ => return Err(Error::BadOperandType),
+    };
+    if bits < 0 || bits.significant_bits() > width {
+        return Err(Error::OutOfRange);
+    }
+    Ok(bits)
+}
+
+/// Pop a non-negative integer parameter (width, integer or fractional bit count)
+fn pop_count(ctx: &mut Context) -> Result<u32> {
+    let value = pop_one_number!(ctx);
+    let f = value.to_f64();
+    if !f.is_finite() || !(0.0..=65536.0).contains(&f) || f.fract() != 0.0 {
+        return Err(Error::OutOfRange);
+    }
+    Ok(f as u32)
+}
+
+/// Encode the number on the stack into an IEEE-754 bit pattern shown in hex
+fn to_ieee_bits(ctx: &mut Context, format: IeeeFormat) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let value = pop_one_number!(ctx);
+    let bits = format.encode(&value);
+    let precision = ctx.config.precision_bits.max(format.width());
+    push_number!(ctx, Float::with_val(precision, &bits), 16);
+    Ok(())
+}
+
+/// Decode the IEEE-754 bit pattern on the stack into a number
+fn from_ieee_bits(ctx: &mut Context, format: IeeeFormat) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let bits = pop_bit_pattern(ctx, format.width())?;
+    let value = format.decode(&bits, ctx.config.precision_bits);
+    push_number!(ctx, value);
+    Ok(())
+}
+
+/// ->f16bits: encode a number as an IEEE-754 binary16 bit pattern
+/// Stack: x -> bits
+pub fn to_f16_bits(ctx: &mut Context) -> Result<()> {
+    to_ieee_bits(ctx, BINARY16)
+}
+
+/// ->f32bits: encode a number as an IEEE-754 binary32 bit pattern
+/// Stack: x -> bits
+pub fn to_f32_bits(ctx: &mut Context) -> Result<()> {
+    to_ieee_bits(ctx, BINARY32)
+}
+
+/// ->f64bits: encode a number as an IEEE-754 binary64 bit pattern
+/// Stack: x -> bits
+pub fn to_f64_bits(ctx: &mut Context) -> Result<()> {
+    to_ieee_bits(ctx, BINARY64)
+}
+
+/// ->f128bits: encode a number as an IEEE-754 binary128 bit pattern
+/// Stack: x -> bits
+pub fn to_f128_bits(ctx: &mut Context) -> Result<()> {
+    to_ieee_bits(ctx, BINARY128)
+}
+
+/// f16bits->: decode an IEEE-754 binary16 bit pattern
+/// Stack: bits -> x
+pub fn from_f16_bits(ctx: &mut Context) -> Result<()> {
+    from_ieee_bits(ctx, BINARY16)
+}
+
+/// f32bits->: decode an IEEE-754 binary32 bit pattern
+/// Stack: bits -> x
+pub fn from_f32_bits(ctx: &mut Context) -> Result<()> {
+    from_ieee_bits(ctx, BINARY32)
+}
+
+/// f64bits->: decode an IEEE-754 binary64 bit pattern
+/// Stack: bits -> x
+pub fn from_f64_bits(ctx: &mut Context) -> Result<()> {
+    from_ieee_bits(ctx, BINARY64)
+}
+
+/// f128bits->: decode an IEEE-754 binary128 bit pattern
+/// Stack: bits -> x
+pub fn from_f128_bits(ctx: &mut Context) -> Result<()> {
+    from_ieee_bits(ctx, BINARY128)
+}
+
+/// fsplit: split an IEEE-754 bit pattern into sign, unbiased exponent and fraction
+/// Stack: bits width -> sign exponent fraction
+/// width is 16, 32, 64 or 128, the fraction field is shown in hex
+/// Zeros and subnormals give the minimum exponent, infinities and nans give bias+1
+pub fn fsplit(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 2);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+
+    let width = pop_count(ctx)?;
+    let format = IeeeFormat::from_width(width).ok_or(Error::OutOfRange)?;
+    let bits = pop_bit_pattern(ctx, format.width())?;
+
+    let (sign, exp, fraction) = format.fields(&bits);
+    let exponent = if exp == 0 {
+        format.emin()
+    } else {
+        exp as i32 - format.bias()
+    };
+
+    let precision = ctx.config.precision_bits.max(format.width());
+    push_number!(ctx, Float::with_val(precision, sign));
+    push_number!(ctx, Float::with_val(precision, exponent));
+    push_number!(ctx, Float::with_val(precision, &fraction), 16);
+    Ok(())
+}
+
+/// ->qmn: quantize a number to Qm.n signed fixed point
+/// Stack: x m n -> code status
+/// code is the two's complement value on 1+m+n bits, shown in hex
+/// status is 0 if exact, 1 if rounded (to nearest even), 2 if saturated
+pub fn to_qmn(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 3);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+    arg_must_be!(ctx, 2, Number);
+
+    let frac_bits = pop_count(ctx)?;
+    let int_bits = pop_count(ctx)?;
+    let value = pop_one_number!(ctx);
+
+    if value.is_nan() {
+        return Err(Error::BadValue("nan cannot be quantized".to_string()));
+    }
+
+    let width = 1 + int_bits + frac_bits;
+    let max_code = (Integer::from(1) << (width - 1)) - 1u32;
+    let min_code = -(Integer::from(1) << (width - 1));
+
+    let scaled = value << frac_bits;
+    let (code, status) = if scaled > max_code {
+        (max_code, 2)
+    } else if scaled < min_code {
+        (min_code, 2)
+    } else {
+        let rounded = scaled.clone().round_even();
+        let status = if rounded == scaled { 0 } else { 1 };
+        let code = rounded.to_integer().unwrap();
+        // Rounding may reach the next code past the positive limit
+        if code > max_code {
+            (max_code, 2)
+        } else {
+            (code, status)
+        }
+    };
+
+    let precision = ctx.config.precision_bits.max(width);
+    push_number!(ctx, Float::with_val(precision, &code.keep_bits(width)), 16);
+    push_number!(ctx, Float::with_val(ctx.config.precision_bits, status));
+    Ok(())
+}
+
+/// qmn->: convert a Qm.n signed fixed point code to a number
+/// Stack: code m n -> x
+/// code is read as two's complement on 1+m+n bits
+pub fn from_qmn(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 3);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+    arg_must_be!(ctx, 2, Number);
+
+    let frac_bits = pop_count(ctx)?;
+    let int_bits = pop_count(ctx)?;
+    let width = 1 + int_bits + frac_bits;
+
+    let mut code = pop_bit_pattern(ctx, width)?;
+    if code.get_bit(width - 1) {
+        code -= Integer::from(1) << width;
+    }
+
+    let precision = ctx.config.precision_bits.max(width);
+    let value = Float::with_val(precision, &code) >> frac_bits;
+    push_number!(ctx, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_matches_native() {
+        for x in [1.0f64, -2.5, 0.1, 1e-310, f64::MAX, f64::MIN_POSITIVE] {
+            let bits = BINARY64.encode(&Float::with_val(128, x));
+            assert_eq!(bits.to_u64().unwrap(), x.to_bits());
+
+            let bits = BINARY32.encode(&Float::with_val(128, x as f32));
+            assert_eq!(bits.to_u32().unwrap(), (x as f32).to_bits());
+        }
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        for x in [1.0f64, -2.5, 0.1, 1e-310, f64::MAX] {
+            let value = BINARY64.decode(&Integer::from(x.to_bits()), 128);
+            assert_eq!(value.to_f64(), x);
+        }
+    }
+
+    #[test]
+    fn test_encode_binary16() {
+        // 65504 is the largest binary16 value, 65520 rounds to infinity
+        let max = BINARY16.encode(&Float::with_val(128, 65504));
+        assert_eq!(max, 0x7bff);
+        let inf = BINARY16.encode(&Float::with_val(128, 65520));
+        assert_eq!(inf, 0x7c00);
+    }
+}
@@ -3,11 +3,15 @@
 
 use crate::context::Context;
 use crate::error::{Error, Result};
-use crate::number::DisplayMode;
+use crate::number::{AngleMode, ComplexFormat, DisplayMode, DEFAULT_MAX_DEPTH};
 use crate::object::Object;
+use rug::float::Round;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Highest maximum depth accepted by maxdepth
+const MAX_DEPTH_LIMIT: f64 = 1_000_000.0;
+
 /// Quit: quit, q, exit
 pub fn quit(_ctx: &mut Context) -> Result<()> {
     Err(Error::Goodbye)
@@ -60,12 +64,22 @@ pub fn help(ctx: &mut Context) -> Result<()> {
     println!("  {K}%inv{R}                    Inverse percent");
     println!("  {K}fact{R}                    Factorial");
     println!("  {K}floor{R}, {K}ceil{R}, {K}round{R}     Rounding");
+    println!("  {K}rnd{R}, {K}trnc{R}               Round/truncate to n decimals or -n digits");
     println!("  {K}ip{R}, {K}fp{R}                  Integer and fractional parts");
     println!("  {K}mant{R}, {K}xpon{R}              Mantissa and exponent");
 
     // Bitwise operations
     println!("\n{T}BITWISE{R}");
     println!("  {O}&{R}, {O}|{R}, {O}^{R}, {O}~{R}              And, or, xor, not (complement)");
+    println!("  {K}sl{R}, {K}sr{R}, {K}asr{R}             Shift left, logical and arithmetic shift right");
+    println!("  {K}rl{R}, {K}rr{R}                  Rotate left, right within word size");
+    println!("  {K}bset{R}, {K}bclr{R}, {K}btst{R}        Set, clear, test a bit");
+    println!("  {K}bfld{R}                    Extract a bit field");
+    println!(
+        "  {K}popcnt{R}, {K}clz{R}, {K}ctz{R}        Count set bits, leading and trailing zeros"
+    );
+    println!("  {K}bswap{R}, {K}brev{R}             Byte swap, bit reversal within word size");
+    println!("  {K}stws{R}, {K}rcws{R}              Set, recall word size in bits");
 
     // Stack operations
     println!("\n{T}STACK{R}");
@@ -95,6 +109,18 @@ pub fn help(ctx: &mut Context) -> Result<()> {
     println!("  {K}c->r{R}, {K}r->c{R}              real to complex, complex to real");
     println!("  {K}p->r{R}, {K}r->p{R}              polar / rectangular conversions");
 
+    // Encoding
+    println!("\n{T}ENCODING{R}");
+    println!("  {K}->f32bits{R}, {K}f32bits->{R}    IEEE-754 bit patterns (also f16, f64, f128)");
+    println!("  {K}fsplit{R}                  Split a bit pattern into sign, exponent, fraction");
+    println!("  {K}->qmn{R}, {K}qmn->{R}            Qm.n fixed point quantization and conversion");
+
+    // Sexagesimal
+    println!("\n{T}SEXAGESIMAL{R}");
+    println!("  {K}->hms{R}, {K}hms->{R}            Decimal hours to/from H.MMSS");
+    println!("  {K}hms+{R}, {K}hms-{R}              Add/subtract in H.MMSS");
+    println!("  {K}->dms{R}, {K}dms->{R}, {K}dms+{R}, {K}dms-{R}  Same for angles in D.MMSS");
+
     // Trigonometry
     println!("\n{T}TRIGONOMETRY{R}");
     println!("  {K}sin{R}, {K}cos{R}, {K}tan{R}           Sine, cosine, tangent");
@@ -132,28 +158,48 @@ pub fn help(ctx: &mut Context) -> Result<()> {
     // Control flow
     println!("\n{T}CONTROL FLOW{R}");
     println!("  {K}if{R}, {K}then{R}, {K}else{R}, {K}end{R}     Conditional execution");
+    println!("  {K}iferr{R}, {K}then{R}, {K}else{R}, {K}end{R}  Error trap");
+    println!("  {K}case{R}, {K}then{R}, {K}end{R}         Multi-way branch");
     println!("  {K}ift{R}, {K}ifte{R}               Inline conditionals");
     println!("  {K}for{R}, {K}next{R}               For loop with variable");
     println!("  {K}start{R}, {K}next{R}             For loop without variable");
     println!("  {K}step{R}                    For loop with custom step");
     println!("  {K}while{R}, {K}repeat{R}, {K}end{R}      While loop");
     println!("  {K}do{R}, {K}until{R}, {K}end{R}          Do-until loop");
+    println!("  {K}break{R}, {K}continue{R}         Leave a loop or go on with its next iteration");
+    println!("  {K}return{R}                  Leave the program");
 
     // Programs
     println!("\n{T}PROGRAMS{R}");
     println!("  {P}<< ... >>{R}               Program notation");
     println!("  {P}« ... »{R}                 Alternative delimiter");
     println!("  {K}eval{R}                    Execute a program");
+    println!("  {K}maxdepth{R}                Set maximum depth of program calls");
+    println!("  {K}halt{R}, {K}cont{R}, {K}kill{R}        Suspend, resume or abandon a program");
+    println!("  {K}sst{R}, {K}sst↓{R}               Run the next object of a halted program");
+    println!("  {K}trace{R}, {K}notrace{R}          Print each object run, or not");
+    println!("  {K}profile{R}                 Run a program, report keyword calls and times");
+    println!("  {K}tevel{R}                   Run a program, push its run time in seconds");
 
     // Configuration
     println!("\n{T}CONFIGURATION{R}");
     println!("  {K}std{R}                     Standard display mode");
     println!("  {K}fix{R}                     Fixed-point mode");
     println!("  {K}sci{R}                     Scientific notation");
+    println!("  {K}eng{R}, {K}si{R}                 Engineering notation, SI prefixes");
+    println!("  {K}grp{R}, {K}nogrp{R}              Digit grouping on or off");
+    println!("  {K}dot{R}, {K}comma{R}              Decimal separator");
+    println!("  {K}verify{R}, {K}noverify{R}        Verified display on or off");
+    println!("  {K}pair{R}, {K}rect{R}, {K}rectj{R}, {K}polar{R}  Complex display (re,im), a+bi, a+bj, r∠θ");
     println!("  {K}prec{R}                    Set precision (bits)");
+    println!("  {K}deg{R}, {K}rad{R}, {K}grad{R}          Set angle mode");
+    println!("  {K}cmplx{R}, {K}real{R}             Complex or nan results out of real domain");
+    println!("  {K}rnear{R}, {K}rup{R}, {K}rdown{R}, {K}rzero{R}  Set rounding direction");
+    println!("  {K}modsto{R}, {K}modrcl{R}          Set, recall modulus of integer arithmetic");
     println!("  {K}default{R}                 Reset to defaults");
     println!("  {K}hex{R}, {K}dec{R}, {K}bin{R}           Set number base");
     println!("  {K}base{R}                    Set arbitrary base ({N}2{R}-{N}62{R})");
+    println!("  {K}ibase{R}                   Set base of unprefixed input numbers");
     println!("  {K}type{R}                    Show object type");
 
     // General
@@ -164,15 +210,44 @@ pub fn help(ctx: &mut Context) -> Result<()> {
     println!("  {K}uname{R}                   Show system info");
     println!("  {K}error{R}                   Push last error code");
     println!("  {K}strerror{R}                Push last error message");
+    println!("  {K}errn{R}, {K}errm{R}, {K}err0{R}        Last error code, full message, clear it");
+    println!("  {K}doerr{R}                   Raise an error code or message");
     println!("  {K}test{R}                    Run test file");
     println!("  {K}quit{R}, {K}q{R}, {K}exit{R}           Exit rpnx");
 
     println!("\n{T}CURRENT SETTINGS{R}");
     println!("  Mode: {:?}", ctx.config.mode);
+    println!("  Angle mode: {:?}", ctx.config.angle_mode);
+    println!("  Complex display: {:?}", ctx.config.complex_format);
+    println!(
+        "  Out of real domain: {}",
+        if ctx.config.complex_results {
+            "complex"
+        } else {
+            "nan"
+        }
+    );
+    println!("  Rounding: {:?}", ctx.config.rounding);
+    println!(
+        "  Digit grouping: {}, decimal separator: '{}'",
+        if ctx.config.grouping { "on" } else { "off" },
+        ctx.config.decimal_separator
+    );
     println!(
         "  Precision: {N}{}{R} bits ({N}{}{R} decimal digits)",
         ctx.config.precision_bits, ctx.config.digits
     );
+    println!("  Word size: {N}{}{R} bits", ctx.config.word_size);
+    println!("  Input base: {N}{}{R}", ctx.config.input_base);
+    println!("  Maximum depth: {N}{}{R}", ctx.config.max_depth);
+    println!(
+        "  Verified display: {}",
+        if ctx.config.verified { "on" } else { "off" }
+    );
+    println!("  Trace: {}", if ctx.config.trace { "on" } else { "off" });
+    if let Some(modulus) = &ctx.config.modulus {
+        println!("  Modulus: {N}{}{R}", modulus);
+    }
     println!("\nFor more information: \x1b]8;;https://github.com/louisrubet/rpnx\x1b\\\x1b[4mhttps://github.com/louisrubet/rpnx\x1b[0m\x1b]8;;\x1b\\");
     Ok(())
 }
@@ -278,6 +353,126 @@ pub fn sci_mode(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// Engineering mode: eng
+pub fn eng_mode(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let digits = match ctx.stack.pop().unwrap() {
+        Object::Number { value, .. } => {
+            let f = value.to_f64();
+            if !f.is_finite() || f.fract() != 0.0 {
+                return Err(Error::BadOperandType);
+            }
+            if f < 0.0 {
+                return Err(Error::OutOfRange);
+            }
+            f as i32
+        }
+        _ => unreachable!(),
+    };
+
+    ctx.config.mode = DisplayMode::Eng;
+    ctx.config.digits = digits;
+    Ok(())
+}
+
+/// SI prefix mode: si (engineering mode with SI prefixes)
+pub fn si_mode(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let digits = match ctx.stack.pop().unwrap() {
+        Object::Number { value, .. } => {
+            let f = value.to_f64();
+            if !f.is_finite() || f.fract() != 0.0 {
+                return Err(Error::BadOperandType);
+            }
+            if f < 0.0 {
+                return Err(Error::OutOfRange);
+            }
+            f as i32
+        }
+        _ => unreachable!(),
+    };
+
+    ctx.config.mode = DisplayMode::Si;
+    ctx.config.digits = digits;
+    Ok(())
+}
+
+/// Grouping: grp (group digits by thousands in decimal, by 4 digits in other bases)
+pub fn grouping_on(ctx: &mut Context) -> Result<()> {
+    ctx.config.grouping = true;
+    Ok(())
+}
+
+/// No grouping: nogrp (display digits without group separators)
+pub fn grouping_off(ctx: &mut Context) -> Result<()> {
+    ctx.config.grouping = false;
+    Ok(())
+}
+
+/// Decimal point: dot (decimal separator is '.')
+pub fn decimal_dot(ctx: &mut Context) -> Result<()> {
+    ctx.config.decimal_separator = '.';
+    Ok(())
+}
+
+/// Decimal comma: comma (decimal separator is ',')
+pub fn decimal_comma(ctx: &mut Context) -> Result<()> {
+    ctx.config.decimal_separator = ',';
+    Ok(())
+}
+
+/// Verified display: verify (re-run command lines at higher precision until digits are stable)
+pub fn verify_on(ctx: &mut Context) -> Result<()> {
+    ctx.config.verified = true;
+    Ok(())
+}
+
+/// Plain display: noverify (display digits as computed)
+pub fn verify_off(ctx: &mut Context) -> Result<()> {
+    ctx.config.verified = false;
+    Ok(())
+}
+
+/// Trace: trace (print each object run with the stack depth and top values)
+pub fn trace_on(ctx: &mut Context) -> Result<()> {
+    ctx.config.trace = true;
+    Ok(())
+}
+
+/// No trace: notrace (run objects silently)
+pub fn trace_off(ctx: &mut Context) -> Result<()> {
+    ctx.config.trace = false;
+    Ok(())
+}
+
+/// Pair display: pair (display complexes as (re,im))
+pub fn complex_pair(ctx: &mut Context) -> Result<()> {
+    ctx.config.complex_format = ComplexFormat::Pair;
+    Ok(())
+}
+
+/// Rectangular display: rect (display complexes as a+bi)
+pub fn complex_rect(ctx: &mut Context) -> Result<()> {
+    ctx.config.complex_format = ComplexFormat::RectI;
+    Ok(())
+}
+
+/// Rectangular display: rectj (display complexes as a+bj)
+pub fn complex_rectj(ctx: &mut Context) -> Result<()> {
+    ctx.config.complex_format = ComplexFormat::RectJ;
+    Ok(())
+}
+
+/// Polar display: polar (display complexes as (r∠θ), θ in the current angle mode)
+pub fn complex_polar(ctx: &mut Context) -> Result<()> {
+    ctx.config.complex_format = ComplexFormat::Polar;
+    Ok(())
+}
+
 /// Precision: prec (set bit precision)
 pub fn precision(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
@@ -304,11 +499,77 @@ pub fn precision(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// Degrees: deg (trigonometric functions use degrees)
+pub fn deg_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.angle_mode = AngleMode::Deg;
+    Ok(())
+}
+
+/// Radians: rad (trigonometric functions use radians)
+pub fn rad_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.angle_mode = AngleMode::Rad;
+    Ok(())
+}
+
+/// Gradians: grad (trigonometric functions use gradians)
+pub fn grad_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.angle_mode = AngleMode::Grad;
+    Ok(())
+}
+
+/// Complex results: cmplx (real domain errors give complex results)
+pub fn cmplx_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.complex_results = true;
+    Ok(())
+}
+
+/// Real results: real (real domain errors give nan)
+pub fn real_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.complex_results = false;
+    Ok(())
+}
+
+/// Round to nearest: rnear (real operations round to nearest, ties to even)
+pub fn round_nearest_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.rounding = Round::Nearest;
+    Ok(())
+}
+
+/// Round up: rup (real operations round toward +inf)
+pub fn round_up_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.rounding = Round::Up;
+    Ok(())
+}
+
+/// Round down: rdown (real operations round toward -inf)
+pub fn round_down_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.rounding = Round::Down;
+    Ok(())
+}
+
+/// Round toward zero: rzero (real operations round toward zero)
+pub fn round_zero_mode(ctx: &mut Context) -> Result<()> {
+    ctx.config.rounding = Round::Zero;
+    Ok(())
+}
+
 /// Default: default (reset to default settings)
 pub fn default(ctx: &mut Context) -> Result<()> {
     ctx.config.mode = DisplayMode::Std;
     ctx.config.precision_bits = 128;
     ctx.config.digits = 38;
+    ctx.config.word_size = 64;
+    ctx.config.angle_mode = AngleMode::Rad;
+    ctx.config.complex_results = true;
+    ctx.config.rounding = Round::Nearest;
+    ctx.config.modulus = None;
+    ctx.config.grouping = false;
+    ctx.config.decimal_separator = '.';
+    ctx.config.verified = false;
+    ctx.config.complex_format = ComplexFormat::Pair;
+    ctx.config.input_base = 10;
+    ctx.config.max_depth = DEFAULT_MAX_DEPTH;
+    ctx.config.trace = false;
     Ok(())
 }
 
@@ -404,6 +665,106 @@ pub fn base(ctx: &mut Context) -> Result<()> {
     }
 }
 
+/// Input base: ibase (base of numbers entered without a 0x, 0b or Nb prefix)
+pub fn input_base(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let new_base = match ctx.stack.pop().unwrap() {
+        Object::Number { value, .. } => {
+            let f = value.to_f64();
+            if !(2.0..=62.0).contains(&f) || f.fract() != 0.0 {
+                return Err(Error::OutOfRange);
+            }
+            f as u8
+        }
+        _ => unreachable!(),
+    };
+
+    ctx.config.input_base = new_base;
+    Ok(())
+}
+
+/// Maximum depth: maxdepth (maximum number of programs being run, calling each other)
+pub fn max_depth(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let new_depth = match ctx.stack.pop().unwrap() {
+        Object::Number { value, .. } => {
+            let f = value.to_f64();
+            if !(1.0..=MAX_DEPTH_LIMIT).contains(&f) || f.fract() != 0.0 {
+                return Err(Error::OutOfRange);
+            }
+            f as usize
+        }
+        _ => unreachable!(),
+    };
+
+    ctx.config.max_depth = new_depth;
+    Ok(())
+}
+
+/// Set word size: stws (word size in bits for rotates, byte swap and bit counts)
+/// Stack: n -> (empty)
+pub fn stws(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let bits = match ctx.stack.pop().unwrap() {
+        Object::Number { value, .. } => {
+            let f = value.to_f64();
+            if !f.is_finite() || !(1.0..=65536.0).contains(&f) || f.fract() != 0.0 {
+                return Err(Error::OutOfRange);
+            }
+            f as u32
+        }
+        _ => unreachable!(),
+    };
+
+    ctx.config.word_size = bits;
+    Ok(())
+}
+
+/// Set modulus: modsto (integer arithmetic is done modulo n, 0 turns modular mode off)
+/// Stack: n -> (empty)
+pub fn modsto(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let modulus = match ctx.stack.pop().unwrap() {
+        Object::Number { value, .. } => match value.to_integer() {
+            Some(n) if value.is_integer() && (n == 0 || n >= 2) => n,
+            _ => return Err(Error::OutOfRange),
+        },
+        _ => unreachable!(),
+    };
+
+    ctx.config.modulus = if modulus == 0 { None } else { Some(modulus) };
+    Ok(())
+}
+
+/// Recall modulus: modrcl (0 when modular mode is off)
+/// Stack: -> n
+pub fn modrcl(ctx: &mut Context) -> Result<()> {
+    use rug::Float;
+
+    let modulus = ctx.config.modulus.clone().unwrap_or_default();
+    let precision = ctx.config.precision_bits.max(modulus.significant_bits());
+    push_number!(ctx, Float::with_val(precision, &modulus));
+    Ok(())
+}
+
+/// Recall word size: rcws
+/// Stack: -> n
+pub fn rcws(ctx: &mut Context) -> Result<()> {
+    use rug::Float;
+
+    let value = Float::with_val(ctx.config.precision_bits, ctx.config.word_size);
+    push_number!(ctx, value);
+    Ok(())
+}
+
 /// Test: test (run tests from file)
 /// Stack: "filename" or 'filename' -> (empty)
 pub fn test(ctx: &mut Context) -> Result<()> {
@@ -580,6 +941,56 @@ pub fn strerror(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// Error message: errm (push last error message as symbol, empty when no error)
+/// Stack: -> 'error message'
+pub fn error_message(ctx: &mut Context) -> Result<()> {
+    let msg = match &ctx.last_error {
+        Some(e) => e.to_string(),
+        None => String::new(),
+    };
+
+    ctx.stack.push(Object::Symbol {
+        name: msg,
+        auto_eval: false,
+    });
+
+    // Preserve last_error so it can be queried again
+    ctx.preserve_last_error = true;
+
+    Ok(())
+}
+
+/// Clear error: err0 (forget the last error, errn gives 0 afterwards)
+pub fn clear_error(ctx: &mut Context) -> Result<()> {
+    ctx.last_error = None;
+    ctx.preserve_last_error = false;
+    Ok(())
+}
+
+/// Raise error: doerr (raise an error from its code or a user error from a message)
+/// Stack: code -> or 'message' ->
+pub fn raise_error(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+
+    match ctx.stack.get(0) {
+        Some(Object::Number { value, .. }) => {
+            let code = value
+                .to_i32_saturating()
+                .filter(|_| value.is_integer())
+                .and_then(Error::from_code)
+                .ok_or(Error::OutOfRange)?;
+            ctx.stack.pop();
+            Err(code)
+        }
+        Some(Object::Symbol { name, .. }) => {
+            let error = Error::UserError(name.clone());
+            ctx.stack.pop();
+            Err(error)
+        }
+        _ => Err(Error::BadOperandType),
+    }
+}
+
 /// History: history (display command history)
 /// Stack: -> (unchanged)
 pub fn history(_ctx: &mut Context) -> Result<()> {
@@ -3,7 +3,10 @@
 
 use crate::context::Context;
 use crate::error::Result;
+use crate::number::rounded;
 use crate::object::Object;
+use crate::operations::complex::push_out_of_domain;
+use rug::float::Round;
 use rug::ops::Pow;
 use rug::Float;
 
@@ -18,8 +21,12 @@ pub fn ln(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.ln();
-            push_number!(ctx, result, base);
+            if value < 0 {
+                push_out_of_domain(ctx, value, base, |z| z.ln());
+            } else {
+                let result = rounded(value, ctx.config.rounding, Float::ln_round);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -50,7 +57,7 @@ pub fn exp(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.exp();
+            let result = rounded(value, ctx.config.rounding, Float::exp_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -82,8 +89,12 @@ pub fn log10(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.log10();
-            push_number!(ctx, result, base);
+            if value < 0 {
+                push_out_of_domain(ctx, value, base, |z| z.log10());
+            } else {
+                let result = rounded(value, ctx.config.rounding, Float::log10_round);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -115,7 +126,12 @@ pub fn alog10(ctx: &mut Context) -> Result<()> {
                 _ => unreachable!(),
             };
             let base = Float::with_val(ctx.config.precision_bits, 10);
-            let result = base.pow(&value);
+            let result = Float::with_val_round(
+                ctx.config.precision_bits,
+                (&base).pow(&value),
+                ctx.config.rounding,
+            )
+            .0;
             push_number!(ctx, result, num_base);
             Ok(())
         }
@@ -152,8 +168,15 @@ pub fn log2(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.log2();
-            push_number!(ctx, result, base);
+            if value < 0 {
+                push_out_of_domain(ctx, value, base, |z| {
+                    let ln2 = Float::with_val(z.prec().0, rug::float::Constant::Log2);
+                    z.ln() / ln2
+                });
+            } else {
+                let result = rounded(value, ctx.config.rounding, Float::log2_round);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -189,7 +212,12 @@ pub fn alog2(ctx: &mut Context) -> Result<()> {
                 _ => unreachable!(),
             };
             let base = Float::with_val(ctx.config.precision_bits, 2);
-            let result = base.pow(&value);
+            let result = Float::with_val_round(
+                ctx.config.precision_bits,
+                (&base).pow(&value),
+                ctx.config.rounding,
+            )
+            .0;
             push_number!(ctx, result, num_base);
             Ok(())
         }
@@ -227,8 +255,12 @@ pub fn lnp1(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.ln_1p();
-            push_number!(ctx, result, base);
+            if value < -1 {
+                push_out_of_domain(ctx, value, base, |z| (z + 1u32).ln());
+            } else {
+                let result = rounded(value, ctx.config.rounding, Float::ln_1p_round);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -263,7 +295,7 @@ pub fn expm(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.exp_m1();
+            let result = rounded(value, ctx.config.rounding, Float::exp_m1_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -314,14 +346,41 @@ pub fn logn(ctx: &mut Context) -> Result<()> {
     let x = pop_one_number!(ctx);
 
     // log_b(x) = ln(x) / ln(b)
-    let ln_x = x.ln();
-    let ln_base = base.ln();
-    let result = ln_x / ln_base;
+    if x < 0 || base < 0 {
+        let precision = ctx.config.precision_bits;
+        push_out_of_domain(ctx, x, num_base, |z| {
+            z.ln() / rug::Complex::with_val(precision, (base, 0)).ln()
+        });
+        return Ok(());
+    }
+    // Round ln(x) and ln(base) so that their quotient stays on the side of the rounding
+    let positive = (x > 1) == (base > 1);
+    let round = match ctx.config.rounding {
+        Round::Zero if positive => Round::Down,
+        Round::Zero => Round::Up,
+        Round::AwayZero if positive => Round::Up,
+        Round::AwayZero => Round::Down,
+        round => round,
+    };
+    let round_x = if base > 1 { round } else { opposite(round) };
+    let round_base = if x > 1 { opposite(round) } else { round };
+    let ln_x = rounded(x, round_x, Float::ln_round);
+    let ln_base = rounded(base, round_base, Float::ln_round);
+    let result = Float::with_val_round(ln_x.prec(), &ln_x / &ln_base, round).0;
 
     push_number!(ctx, result, num_base);
     Ok(())
 }
 
+/// The rounding direction towards the other infinity
+fn opposite(round: Round) -> Round {
+    match round {
+        Round::Up => Round::Down,
+        Round::Down => Round::Up,
+        round => round,
+    }
+}
+
 /// ALOGN: alogn (arbitrary base anti-log, i.e., base^x)
 /// Stack: x base -> base^x
 pub fn alogn(ctx: &mut Context) -> Result<()> {
@@ -338,7 +397,11 @@ pub fn alogn(ctx: &mut Context) -> Result<()> {
     let base = pop_one_number!(ctx);
     let x = pop_one_number!(ctx);
 
-    let result = base.pow(&x);
+    if base < 0 && !x.is_integer() {
+        push_out_of_domain(ctx, base, num_base, |z| z.pow(&x));
+        return Ok(());
+    }
+    let result = Float::with_val_round(base.prec(), (&base).pow(&x), ctx.config.rounding).0;
     push_number!(ctx, result, num_base);
     Ok(())
 }
@@ -444,4 +507,63 @@ mod tests {
             _ => panic!("Expected number"),
         }
     }
+
+    #[test]
+    fn test_alog_rounding() {
+        let mut ctx = Context::new();
+
+        // 10^0.5, 2^0.5 and log_3(2) are not exact, rounded up and down they differ
+        let mut results = Vec::new();
+        for rounding in [rug::float::Round::Up, rug::float::Round::Down] {
+            ctx.config.rounding = rounding;
+            push_number!(ctx, Float::with_val(128, 0.5));
+            alog10(&mut ctx).unwrap();
+            push_number!(ctx, Float::with_val(128, 0.5));
+            alog2(&mut ctx).unwrap();
+            push_number!(ctx, Float::with_val(128, 2));
+            push_number!(ctx, Float::with_val(128, 3));
+            logn(&mut ctx).unwrap();
+            results.push(ctx.stack.clone());
+            ctx.stack.clear();
+        }
+        for level in 0..3 {
+            match (results[0].get(level), results[1].get(level)) {
+                (
+                    Some(Object::Number { value: up, .. }),
+                    Some(Object::Number { value: down, .. }),
+                ) => {
+                    assert!(up > down)
+                }
+                _ => panic!("Expected numbers"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_logn_rounding() {
+        let mut ctx = Context::new();
+
+        // Rounded down, to nearest and up, log_base(x) brackets its value at a higher precision
+        let values = [0.1, 0.3, 0.7, 1.5, 2.0, 3.0, 7.0, 10.0, 1e10];
+        for x in values {
+            for base in values {
+                let exact = Float::with_val(256, Float::with_val(256, x).ln())
+                    / Float::with_val(256, base).ln();
+                let mut results = Vec::new();
+                for rounding in [Round::Down, Round::Nearest, Round::Up] {
+                    ctx.config.rounding = rounding;
+                    push_number!(ctx, Float::with_val(24, x));
+                    push_number!(ctx, Float::with_val(24, base));
+                    logn(&mut ctx).unwrap();
+                    match ctx.stack.pop() {
+                        Some(Object::Number { value, .. }) => results.push(value),
+                        _ => panic!("Expected number"),
+                    }
+                }
+                assert!(results[0] <= exact, "log_{base}({x}) rounded down");
+                assert!(results[0] <= results[1] && results[1] <= results[2]);
+                assert!(results[2] >= exact, "log_{base}({x}) rounded up");
+            }
+        }
+    }
 }
@@ -9,6 +9,7 @@ use std::collections::HashMap;
 // Operation modules
 pub mod branch;
 pub mod complex;
+pub mod encoding;
 pub mod general;
 pub mod logs;
 pub mod program_ops;
@@ -16,8 +17,8 @@ pub mod real;
 pub mod stack_ops;
 pub mod store;
 pub mod test;
+pub mod time;
 pub mod trig;
-// pub mod time;
 
 // Placeholder functions removed - using real implementations from stack_ops.rs
 
@@ -49,16 +50,38 @@ lazy_static! {
         m.insert("floor", real::floor as KeywordFn);
         m.insert("ceil", real::ceil as KeywordFn);
         m.insert("round", real::round as KeywordFn);
+        m.insert("rnd", real::round_to_digits as KeywordFn);
+        m.insert("trnc", real::truncate_to_digits as KeywordFn);
         m.insert("ip", real::integer_part as KeywordFn);
         m.insert("fp", real::fractional_part as KeywordFn);
         m.insert("mant", real::mantissa as KeywordFn);
         m.insert("xpon", real::exponent as KeywordFn);
+        m.insert("ulp", real::ulp as KeywordFn);
+        m.insert("nextup", real::next_up as KeywordFn);
+        m.insert("nextdown", real::next_down as KeywordFn);
+        m.insert("prec?", real::precision_of as KeywordFn);
+        m.insert("exact", real::exact as KeywordFn);
+        m.insert("->bin", real::to_binary as KeywordFn);
 
         // Bitwise operations
         m.insert("&", real::bitwise_and as KeywordFn);
         m.insert("|", real::bitwise_or as KeywordFn);
         m.insert("^", real::bitwise_xor as KeywordFn);
         m.insert("~", real::bitwise_not as KeywordFn);
+        m.insert("sl", real::shift_left as KeywordFn);
+        m.insert("sr", real::shift_right as KeywordFn);
+        m.insert("asr", real::arithmetic_shift_right as KeywordFn);
+        m.insert("rl", real::rotate_left as KeywordFn);
+        m.insert("rr", real::rotate_right as KeywordFn);
+        m.insert("bset", real::bit_set as KeywordFn);
+        m.insert("bclr", real::bit_clear as KeywordFn);
+        m.insert("btst", real::bit_test as KeywordFn);
+        m.insert("bfld", real::bit_field as KeywordFn);
+        m.insert("popcnt", real::popcount as KeywordFn);
+        m.insert("clz", real::count_leading_zeros as KeywordFn);
+        m.insert("ctz", real::count_trailing_zeros as KeywordFn);
+        m.insert("bswap", real::byte_swap as KeywordFn);
+        m.insert("brev", real::bit_reverse as KeywordFn);
 
         // Stack operations (stack_ops.rs)
         m.insert("swap", stack_ops::swap as KeywordFn);
@@ -107,17 +130,50 @@ lazy_static! {
         m.insert("std", general::std_mode as KeywordFn);
         m.insert("fix", general::fix_mode as KeywordFn);
         m.insert("sci", general::sci_mode as KeywordFn);
+        m.insert("eng", general::eng_mode as KeywordFn);
+        m.insert("si", general::si_mode as KeywordFn);
+        m.insert("grp", general::grouping_on as KeywordFn);
+        m.insert("nogrp", general::grouping_off as KeywordFn);
+        m.insert("dot", general::decimal_dot as KeywordFn);
+        m.insert("comma", general::decimal_comma as KeywordFn);
+        m.insert("verify", general::verify_on as KeywordFn);
+        m.insert("noverify", general::verify_off as KeywordFn);
+        m.insert("trace", general::trace_on as KeywordFn);
+        m.insert("notrace", general::trace_off as KeywordFn);
+        m.insert("pair", general::complex_pair as KeywordFn);
+        m.insert("rect", general::complex_rect as KeywordFn);
+        m.insert("rectj", general::complex_rectj as KeywordFn);
+        m.insert("polar", general::complex_polar as KeywordFn);
         m.insert("prec", general::precision as KeywordFn);
+        m.insert("deg", general::deg_mode as KeywordFn);
+        m.insert("rad", general::rad_mode as KeywordFn);
+        m.insert("grad", general::grad_mode as KeywordFn);
+        m.insert("cmplx", general::cmplx_mode as KeywordFn);
+        m.insert("real", general::real_mode as KeywordFn);
+        m.insert("rnear", general::round_nearest_mode as KeywordFn);
+        m.insert("rup", general::round_up_mode as KeywordFn);
+        m.insert("rdown", general::round_down_mode as KeywordFn);
+        m.insert("rzero", general::round_zero_mode as KeywordFn);
         m.insert("default", general::default as KeywordFn);
         m.insert("hex", general::hex as KeywordFn);
         m.insert("dec", general::dec as KeywordFn);
         m.insert("bin", general::bin as KeywordFn);
         m.insert("base", general::base as KeywordFn);
+        m.insert("ibase", general::input_base as KeywordFn);
+        m.insert("maxdepth", general::max_depth as KeywordFn);
+        m.insert("stws", general::stws as KeywordFn);
+        m.insert("rcws", general::rcws as KeywordFn);
+        m.insert("modsto", general::modsto as KeywordFn);
+        m.insert("modrcl", general::modrcl as KeywordFn);
         m.insert("date", general::date as KeywordFn);
         m.insert("time", general::time as KeywordFn);
         m.insert("ticks", general::ticks as KeywordFn);
         m.insert("error", general::error as KeywordFn);
         m.insert("strerror", general::strerror as KeywordFn);
+        m.insert("errn", general::error as KeywordFn);
+        m.insert("errm", general::error_message as KeywordFn);
+        m.insert("err0", general::clear_error as KeywordFn);
+        m.insert("doerr", general::raise_error as KeywordFn);
         m.insert("history", general::history as KeywordFn);
 
         // Variable storage operations (store.rs)
@@ -145,6 +201,29 @@ lazy_static! {
         m.insert("p->r", complex::p_to_r as KeywordFn);
         m.insert("r->p", complex::r_to_p as KeywordFn);
 
+        // Encoding operations (encoding.rs)
+        m.insert("->f16bits", encoding::to_f16_bits as KeywordFn);
+        m.insert("->f32bits", encoding::to_f32_bits as KeywordFn);
+        m.insert("->f64bits", encoding::to_f64_bits as KeywordFn);
+        m.insert("->f128bits", encoding::to_f128_bits as KeywordFn);
+        m.insert("f16bits->", encoding::from_f16_bits as KeywordFn);
+        m.insert("f32bits->", encoding::from_f32_bits as KeywordFn);
+        m.insert("f64bits->", encoding::from_f64_bits as KeywordFn);
+        m.insert("f128bits->", encoding::from_f128_bits as KeywordFn);
+        m.insert("fsplit", encoding::fsplit as KeywordFn);
+        m.insert("->qmn", encoding::to_qmn as KeywordFn);
+        m.insert("qmn->", encoding::from_qmn as KeywordFn);
+
+        // Sexagesimal operations (time.rs)
+        m.insert("->hms", time::to_hms as KeywordFn);
+        m.insert("hms->", time::from_hms as KeywordFn);
+        m.insert("hms+", time::hms_add as KeywordFn);
+        m.insert("hms-", time::hms_sub as KeywordFn);
+        m.insert("->dms", time::to_hms as KeywordFn); // Alias for ->hms on angles
+        m.insert("dms->", time::from_hms as KeywordFn); // Alias for hms-> on angles
+        m.insert("dms+", time::hms_add as KeywordFn); // Alias for hms+ on angles
+        m.insert("dms-", time::hms_sub as KeywordFn); // Alias for hms- on angles
+
         // Trigonometric operations (trig.rs)
         m.insert("sin", trig::sin as KeywordFn);
         m.insert("cos", trig::cos as KeywordFn);
@@ -181,6 +260,14 @@ lazy_static! {
 
         // Program operations (program_ops.rs)
         m.insert("eval", program_ops::eval as KeywordFn);
+        m.insert("halt", program_ops::halt as KeywordFn);
+        m.insert("cont", program_ops::cont as KeywordFn);
+        m.insert("sst", program_ops::sst as KeywordFn);
+        m.insert("sst↓", program_ops::sst_over as KeywordFn);
+        m.insert("kill", program_ops::kill as KeywordFn);
+        m.insert("profile", program_ops::profile as KeywordFn);
+        m.insert("tevel", program_ops::tevel as KeywordFn);
+        m.insert("teval", program_ops::tevel as KeywordFn); // Alias for tevel
 
         m
     };
@@ -195,6 +282,12 @@ lazy_static! {
         m.insert("else", branch::rpnx_else as BranchFn);
         m.insert("end", branch::rpnx_end as BranchFn);
 
+        // IFERR/THEN/ELSE/END error trap
+        m.insert("iferr", branch::rpnx_iferr as BranchFn);
+
+        // CASE/THEN/END multi-way branch
+        m.insert("case", branch::rpnx_case as BranchFn);
+
         // Inline IF operations
         m.insert("ift", branch::rpnx_ift as BranchFn);
         m.insert("ifte", branch::rpnx_ifte as BranchFn);
@@ -213,6 +306,11 @@ lazy_static! {
         m.insert("do", branch::rpnx_do as BranchFn);
         m.insert("until", branch::rpnx_until as BranchFn);
 
+        // Loop and program exits
+        m.insert("break", branch::rpnx_break as BranchFn);
+        m.insert("continue", branch::rpnx_continue as BranchFn);
+        m.insert("return", branch::rpnx_return as BranchFn);
+
         m
     };
 }
@@ -2,8 +2,11 @@
 // Program evaluation operations
 
 use crate::context::Context;
-use crate::error::Result;
-use crate::object::Object;
+use crate::error::{Error, Result};
+use crate::object::{Object, Program};
+use crate::program::{self, Profile, Run};
+use rug::Float;
+use std::time::Instant;
 
 /// EVAL: eval (execute/evaluate any object)
 /// Stack: object -> (executes or evaluates object)
@@ -18,57 +21,8 @@ pub fn eval(ctx: &mut Context) -> Result<()> {
     let obj = ctx.stack.pop().unwrap();
 
     match obj {
-        // Program: execute it
-        Object::Program(program) => {
-            // Check if program has local variables
-            if !program.local_vars.is_empty() {
-                // First, execute prefix code (e.g., dup2 *)
-                if !program.prefix_objects.is_empty() {
-                    crate::program::run_objects(&program.prefix_objects, ctx)?;
-                }
-
-                // Now pop values from stack for local variables
-                let num_vars = program.local_vars.len();
-
-                // Check we have enough values on stack
-                if ctx.stack.len() < num_vars {
-                    return Err(crate::error::Error::MissingOperand);
-                }
-
-                // Pop values and bind to local variables
-                // Variables are bound in order: first variable gets deepest stack value among those being popped
-                // With stack [a, b, c] (c on top) and vars [x, y], x gets b and y gets c
-                let mut values = Vec::new();
-                for _ in 0..num_vars {
-                    values.push(ctx.stack.pop().unwrap());
-                }
-
-                // Reverse to match order: first variable = first popped value from bottom
-                values.reverse();
-
-                // Store in local heap
-                for (var_name, value) in program.local_vars.iter().zip(values.iter()) {
-                    ctx.local_heap.store(var_name.clone(), value.clone());
-                }
-
-                // Execute the program's body objects
-                let result = crate::program::run_objects(&program.objects, ctx);
-
-                // Clean up local variables
-                for var_name in &program.local_vars {
-                    ctx.local_heap.purge(var_name);
-                }
-
-                result
-            } else {
-                // No local variables, execute normally (prefix and body combined)
-                if !program.prefix_objects.is_empty() {
-                    crate::program::run_objects(&program.prefix_objects, ctx)?;
-                }
-                crate::program::run_objects(&program.objects, ctx)?;
-                Ok(())
-            }
-        }
+        // Program: execute it, binding its local variables if declared
+        Object::Program(program) => crate::program::run_program(program, ctx),
 
         // Symbol: recall variable and push value
         Object::Symbol { name, .. } => {
@@ -107,6 +61,114 @@ pub fn eval(ctx: &mut Context) -> Result<()> {
     }
 }
 
+/// HALT: halt
+/// Suspend the command line being run, with its programs, back to the prompt
+/// Only reached in programs run by a command (the evaluator suspends the others)
+pub fn halt(_ctx: &mut Context) -> Result<()> {
+    Err(Error::RuntimeError(
+        "'halt' in a program run by a command".to_string(),
+    ))
+}
+
+/// CONT: cont
+/// Resume the halted command line up to its end or its next HALT
+pub fn cont(ctx: &mut Context) -> Result<()> {
+    program::resume(ctx, Run::Resumable, "cont")
+}
+
+/// SST: sst
+/// Run the next object of the halted command line, stepping into the programs it calls
+pub fn sst(ctx: &mut Context) -> Result<()> {
+    program::resume(ctx, Run::Step, "sst")
+}
+
+/// SST↓: sst↓
+/// Run the next object of the halted command line, with the programs it calls
+pub fn sst_over(ctx: &mut Context) -> Result<()> {
+    program::resume(ctx, Run::StepOver, "sst↓")
+}
+
+/// KILL: kill
+/// Abandon the halted command lines
+pub fn kill(ctx: &mut Context) -> Result<()> {
+    program::kill(ctx)
+}
+
+/// PROFILE: profile
+/// Stack: program ->
+/// Run a program, or the program stored in a variable, then print the calls and cumulative time
+/// of each keyword, slowest first, and the number of objects run
+pub fn profile(ctx: &mut Context) -> Result<()> {
+    let (program, global) = pop_program(ctx)?;
+
+    let outer = ctx.profile.replace(Profile::default());
+    let start = Instant::now();
+    let result = run(program, global, ctx);
+    let elapsed = start.elapsed();
+    let profile = std::mem::replace(&mut ctx.profile, outer).unwrap_or_default();
+    result?;
+
+    let mut keywords: Vec<_> = profile.keywords.into_iter().collect();
+    keywords.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then(a.0.cmp(b.0)));
+    println!("{:<12} {:>10} {:>14}", "keyword", "calls", "time (ms)");
+    for (name, (calls, time)) in keywords {
+        println!(
+            "{:<12} {:>10} {:>14.3}",
+            name,
+            calls,
+            time.as_secs_f64() * 1000.0
+        );
+    }
+    println!(
+        "{} instructions in {:.3} ms",
+        profile.instructions,
+        elapsed.as_secs_f64() * 1000.0
+    );
+    Ok(())
+}
+
+/// TEVEL: tevel
+/// Stack: program -> results time
+/// Run a program, or the program stored in a variable, and push its run time in seconds
+pub fn tevel(ctx: &mut Context) -> Result<()> {
+    let (program, global) = pop_program(ctx)?;
+
+    let start = Instant::now();
+    run(program, global, ctx)?;
+    let nanos = Float::with_val(ctx.config.precision_bits, start.elapsed().as_nanos());
+    let seconds = nanos / 1_000_000_000u32;
+
+    push_number!(ctx, seconds);
+    Ok(())
+}
+
+/// Pop a program, or the name of a variable holding one, with whether it was recalled from a
+/// global variable
+fn pop_program(ctx: &mut Context) -> Result<(Box<Program>, bool)> {
+    min_arguments!(ctx, 1);
+
+    let program = match ctx.stack.get(0).unwrap() {
+        Object::Program(program) => (program.clone(), false),
+        Object::Symbol { name, .. } => match ctx.find_variable(name) {
+            Some(Object::Program(program)) => (program.clone(), !ctx.is_local(name)),
+            Some(_) => return Err(Error::BadOperandType),
+            None => return Err(Error::UnknownVariable(name.clone())),
+        },
+        _ => return Err(Error::BadOperandType),
+    };
+    ctx.stack.pop();
+    Ok(program)
+}
+
+/// Run a program popped by pop_program
+fn run(program: Box<Program>, global: bool, ctx: &mut Context) -> Result<()> {
+    if global {
+        program::run_global_program(program, ctx)
+    } else {
+        program::run_program(program, ctx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
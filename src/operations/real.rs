@@ -3,7 +3,9 @@
 
 use crate::context::Context;
 use crate::error::{Error, Result};
+use crate::number::{format_binary_exponent, format_exact_decimal, rounded};
 use crate::object::Object;
+use crate::operations::complex::push_out_of_domain;
 use rug::ops::Pow;
 use rug::{Complex, Float};
 
@@ -11,6 +13,10 @@ use rug::{Complex, Float};
 pub fn plus(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 2);
 
+    if modular_binary(ctx, |a, b, _| Some(a + b))? {
+        return Ok(());
+    }
+
     // Handle different type combinations
     match (ctx.stack.get(1), ctx.stack.get(0)) {
         // Number + Number
@@ -21,7 +27,7 @@ pub fn plus(ctx: &mut Context) -> Result<()> {
                 _ => 10,
             };
             let (a, b) = pop_two_numbers!(ctx);
-            let result = a + b;
+            let result = Float::with_val_round(a.prec(), &a + &b, ctx.config.rounding).0;
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -183,6 +189,10 @@ pub fn plus(ctx: &mut Context) -> Result<()> {
 pub fn minus(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 2);
 
+    if modular_binary(ctx, |a, b, _| Some(a - b))? {
+        return Ok(());
+    }
+
     match (ctx.stack.get(1), ctx.stack.get(0)) {
         // Number - Number
         (Some(Object::Number { .. }), Some(Object::Number { .. })) => {
@@ -192,7 +202,7 @@ pub fn minus(ctx: &mut Context) -> Result<()> {
                 _ => 10,
             };
             let (a, b) = pop_two_numbers!(ctx);
-            let result = a - b;
+            let result = Float::with_val_round(a.prec(), &a - &b, ctx.config.rounding).0;
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -275,6 +285,10 @@ pub fn minus(ctx: &mut Context) -> Result<()> {
 pub fn multiply(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 2);
 
+    if modular_binary(ctx, |a, b, _| Some(a * b))? {
+        return Ok(());
+    }
+
     match (ctx.stack.get(1), ctx.stack.get(0)) {
         // Number * Number
         (Some(Object::Number { .. }), Some(Object::Number { .. })) => {
@@ -284,7 +298,7 @@ pub fn multiply(ctx: &mut Context) -> Result<()> {
                 _ => 10,
             };
             let (a, b) = pop_two_numbers!(ctx);
-            let result = a * b;
+            let result = Float::with_val_round(a.prec(), &a * &b, ctx.config.rounding).0;
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -367,6 +381,10 @@ pub fn multiply(ctx: &mut Context) -> Result<()> {
 pub fn divide(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 2);
 
+    if modular_binary(ctx, |a, b, m| b.invert(m).ok().map(|b_inv| a * b_inv))? {
+        return Ok(());
+    }
+
     match (ctx.stack.get(1), ctx.stack.get(0)) {
         // Number / Number
         (Some(Object::Number { .. }), Some(Object::Number { .. })) => {
@@ -378,7 +396,7 @@ pub fn divide(ctx: &mut Context) -> Result<()> {
             let (a, b) = pop_two_numbers!(ctx);
 
             // Division by zero produces inf or nan (IEEE 754 behavior)
-            let result = a / b;
+            let result = Float::with_val_round(a.prec(), &a / &b, ctx.config.rounding).0;
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -462,6 +480,10 @@ pub fn divide(ctx: &mut Context) -> Result<()> {
 pub fn neg(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
+    if modular_unary(ctx, |a, _| Some(-a))? {
+        return Ok(());
+    }
+
     let obj = ctx.stack.pop().unwrap();
 
     match obj {
@@ -499,12 +521,22 @@ pub fn neg(ctx: &mut Context) -> Result<()> {
 pub fn inv(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
+    if modular_unary(ctx, |a, m| a.invert(m).ok())? {
+        return Ok(());
+    }
+
     let obj = ctx.stack.pop().unwrap();
 
     match obj {
         Object::Number { value, base } => {
             // Division by zero produces inf (IEEE 754 behavior)
-            let result = Float::with_val(ctx.config.precision_bits, 1) / value;
+            let one = Float::with_val(ctx.config.precision_bits, 1);
+            let result = Float::with_val_round(
+                ctx.config.precision_bits,
+                &one / &value,
+                ctx.config.rounding,
+            )
+            .0;
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -534,6 +566,10 @@ pub fn inv(ctx: &mut Context) -> Result<()> {
 pub fn power(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 2);
 
+    if modular_binary(ctx, |a, b, m| a.pow_mod(&b, m).ok())? {
+        return Ok(());
+    }
+
     let exponent = ctx.stack.pop().unwrap();
     let base_obj = ctx.stack.pop().unwrap();
 
@@ -549,23 +585,10 @@ pub fn power(ctx: &mut Context) -> Result<()> {
             // Check if base is negative and exponent is not an integer
             // In this case, the result will be complex
             if a < 0 && !b.is_integer() {
-                // Convert to complex and compute
-                let base_complex = Complex::with_val(
-                    ctx.config.precision_bits,
-                    (a.clone(), Float::with_val(ctx.config.precision_bits, 0)),
-                );
-                let exp_complex = Complex::with_val(
-                    ctx.config.precision_bits,
-                    (b, Float::with_val(ctx.config.precision_bits, 0)),
-                );
-                let result = base_complex.pow(&exp_complex);
-                ctx.stack.push(Object::Complex {
-                    value: result,
-                    re_base: base_a,
-                    im_base: base_a,
-                });
+                // Compute in the complex plane
+                push_out_of_domain(ctx, a, base_a, |z| z.pow(&b));
             } else {
-                let result = a.pow(b);
+                let result = Float::with_val_round(a.prec(), (&a).pow(&b), ctx.config.rounding).0;
                 push_number!(ctx, result, base_a);
             }
             Ok(())
@@ -658,17 +681,9 @@ pub fn sqrt(ctx: &mut Context) -> Result<()> {
         Object::Number { value, base } => {
             if value < 0 {
                 // Negative real: sqrt(-x) = i * sqrt(x)
-                let abs_val = value.abs();
-                let sqrt_abs = abs_val.sqrt();
-                let zero = Float::with_val(ctx.config.precision_bits, 0);
-                let complex_result = Complex::with_val(ctx.config.precision_bits, (zero, sqrt_abs));
-                ctx.stack.push(Object::Complex {
-                    value: complex_result,
-                    re_base: base,
-                    im_base: base,
-                });
+                push_out_of_domain(ctx, value, base, |z| z.sqrt());
             } else {
-                let result = value.sqrt();
+                let result = rounded(value, ctx.config.rounding, Float::sqrt_round);
                 push_number!(ctx, result, base);
             }
             Ok(())
@@ -701,7 +716,7 @@ pub fn sq(ctx: &mut Context) -> Result<()> {
 
     match obj {
         Object::Number { value, base } => {
-            let result = value.square();
+            let result = rounded(value, ctx.config.rounding, Float::square_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -1039,6 +1054,159 @@ pub fn round(ctx: &mut Context) -> Result<()> {
     }
 }
 
+/// Highest power of ten computed exactly by rnd and trnc, beyond it powers of ten are floats
+/// Also the highest number of digits of an exact expansion
+const MAX_EXACT_DECIMALS: u64 = 100_000;
+
+/// Floor of log10 of a non-zero value
+/// Exponents beyond MAX_EXACT_DECIMALS are estimated with `precision` bits
+fn decimal_exponent(value: &rug::Rational, precision: u32) -> i64 {
+    use rug::{Integer, Rational};
+
+    let abs_val = Rational::from(value.abs_ref());
+    let ten_pow = |e: i64| -> Rational {
+        let p = Rational::from(Integer::from(10).pow(e.unsigned_abs() as u32));
+        if e >= 0 {
+            p
+        } else {
+            p.recip()
+        }
+    };
+
+    // Estimate then fix the off-by-one errors of the estimate
+    let mut e = Float::with_val(precision + 64, &abs_val)
+        .log10()
+        .floor()
+        .to_f64() as i64;
+    if e.unsigned_abs() > MAX_EXACT_DECIMALS {
+        return e;
+    }
+    while ten_pow(e) > abs_val {
+        e -= 1;
+    }
+    while ten_pow(e + 1) <= abs_val {
+        e += 1;
+    }
+    e
+}
+
+/// Round or truncate a value to n decimal places (n >= 0) or -n significant digits (n < 0)
+fn round_digits(value: &Float, n: i32, truncate: bool, precision: u32) -> Float {
+    use rug::{Integer, Rational};
+
+    let exact = match value.to_rational() {
+        Some(exact) if exact != 0 => exact,
+        _ => return Float::with_val(precision, value),
+    };
+    let decimals = if n >= 0 {
+        n as i64
+    } else {
+        -(n as i64) - 1 - decimal_exponent(&exact, precision)
+    };
+
+    // The value has no more decimals than fraction bits, rounding beyond them changes nothing
+    let fraction_bits = (value.prec() as i64 - value.get_exp().unwrap_or(0) as i64).max(0);
+    if decimals >= fraction_bits {
+        return Float::with_val(precision, value);
+    }
+
+    // Significant digits of very large or small values: the power of ten is a float with
+    // enough bits for the digits kept
+    if decimals.unsigned_abs() > MAX_EXACT_DECIMALS {
+        let bits = precision + 4 * n.unsigned_abs() + 64;
+        let scale = Float::with_val(bits, 10).pow(Integer::from(decimals));
+        let scaled = Float::with_val(bits, value * &scale);
+        let digits = if truncate {
+            scaled.trunc()
+        } else {
+            scaled.round()
+        };
+        return Float::with_val(precision, digits / scale);
+    }
+
+    let scale = Rational::from(Integer::from(10).pow(decimals.unsigned_abs() as u32));
+    let scaled = if decimals >= 0 {
+        exact * &scale
+    } else {
+        exact / &scale
+    };
+    // Halfway cases are rounded away from zero
+    let digits = if truncate {
+        scaled.trunc()
+    } else {
+        scaled.round()
+    };
+    let result = if decimals >= 0 {
+        digits / scale
+    } else {
+        digits * scale
+    };
+    Float::with_val(precision, &result)
+}
+
+/// Apply round_digits to the value at level 2 with the digit count at level 1
+fn round_digits_op(ctx: &mut Context, truncate: bool) -> Result<()> {
+    min_arguments!(ctx, 2);
+    arg_must_be!(ctx, 0, Number);
+
+    let n = match ctx.stack.get(0) {
+        Some(Object::Number { value, .. }) => {
+            let f = value.to_f64();
+            if !f.is_finite() || !(-100000.0..=100000.0).contains(&f) || f.fract() != 0.0 {
+                return Err(Error::OutOfRange);
+            }
+            f as i32
+        }
+        _ => unreachable!(),
+    };
+    let precision = ctx.config.precision_bits;
+    match ctx.stack.get(1) {
+        Some(Object::Number { .. }) => {
+            ctx.stack.pop();
+            let (value, base) = match ctx.stack.pop().unwrap() {
+                Object::Number { value, base } => (value, base),
+                _ => unreachable!(),
+            };
+            push_number!(ctx, round_digits(&value, n, truncate, precision), base);
+            Ok(())
+        }
+        Some(Object::Complex { .. }) => {
+            ctx.stack.pop();
+            let (value, re_base, im_base) = match ctx.stack.pop().unwrap() {
+                Object::Complex {
+                    value,
+                    re_base,
+                    im_base,
+                } => (value, re_base, im_base),
+                _ => unreachable!(),
+            };
+            let re = round_digits(value.real(), n, truncate, precision);
+            let im = round_digits(value.imag(), n, truncate, precision);
+            ctx.stack.push(Object::Complex {
+                value: Complex::with_val(precision, (re, im)),
+                re_base,
+                im_base,
+            });
+            Ok(())
+        }
+        _ => Err(Error::BadOperandType),
+    }
+}
+
+/// Round digits: rnd (round to n decimal places, or to -n significant digits if n < 0)
+/// Stack: x n -> rounded x
+/// For complex numbers, applies to both real and imaginary parts
+pub fn round_to_digits(ctx: &mut Context) -> Result<()> {
+    round_digits_op(ctx, false)
+}
+
+/// Truncate digits: trnc (truncate to n decimal places, or to -n significant digits if n < 0)
+/// Stack: x n -> truncated x
+/// For complex numbers, applies to both real and imaginary parts
+pub fn truncate_to_digits(ctx: &mut Context) -> Result<()> {
+    round_digits_op(ctx, true)
+}
+
 /// Integer part: ip
 pub fn integer_part(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
@@ -1139,6 +1307,103 @@ pub fn exponent(ctx: &mut Context) -> Result<()> {
     }
 }
 
+/// Unit in the last place: ulp (weight of the last mantissa bit of x)
+pub fn ulp(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let (value, base) = match ctx.stack.pop().unwrap() {
+        Object::Number { value, base } => (value, base),
+        _ => unreachable!(),
+    };
+    // Zero and special values have no exponent - return error 4 (OutOfRange)
+    let exp = value.get_exp().ok_or(Error::OutOfRange)?;
+
+    // value = 0.1xxx * 2^exp, the last of its prec bits weighs 2^(exp-prec)
+    let ulp = Float::with_val(value.prec(), 1) << (exp - value.prec() as i32);
+    push_number!(ctx, ulp, base);
+    Ok(())
+}
+
+/// Next float up: nextup (smallest float greater than x at its precision)
+pub fn next_up(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let (mut value, base) = match ctx.stack.pop().unwrap() {
+        Object::Number { value, base } => (value, base),
+        _ => unreachable!(),
+    };
+    value.next_up();
+    push_number!(ctx, value, base);
+    Ok(())
+}
+
+/// Next float down: nextdown (largest float less than x at its precision)
+pub fn next_down(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let (mut value, base) = match ctx.stack.pop().unwrap() {
+        Object::Number { value, base } => (value, base),
+        _ => unreachable!(),
+    };
+    value.next_down();
+    push_number!(ctx, value, base);
+    Ok(())
+}
+
+/// Precision of an object: prec? (bits of a number or of complex parts)
+pub fn precision_of(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+
+    let precision = match ctx.stack.get(0) {
+        Some(Object::Number { value, .. }) => value.prec(),
+        Some(Object::Complex { value, .. }) => value.prec().0,
+        _ => return Err(Error::BadOperandType),
+    };
+    ctx.stack.pop();
+    push_number!(ctx, Float::with_val(ctx.config.precision_bits, precision));
+    Ok(())
+}
+
+/// Exact value: exact (exact decimal expansion of the stored binary float)
+pub fn exact(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let value = pop_one_number!(ctx);
+
+    // The expansion has a decimal per fraction bit, and less than 0.31 digit per integer bit
+    if let Some(exp) = value.get_exp() {
+        let exp = i64::from(exp);
+        let fraction_bits = (i64::from(value.prec()) - exp).max(0) as u64;
+        let integer_digits = exp.max(0) as u64 * 31 / 100;
+        if fraction_bits + integer_digits > MAX_EXACT_DECIMALS {
+            return Err(Error::OutOfRange);
+        }
+    }
+
+    ctx.stack.push(Object::Symbol {
+        name: format_exact_decimal(&value),
+        auto_eval: false,
+    });
+    Ok(())
+}
+
+/// Binary value: ->bin (binary mantissa and power of 2 exponent of the stored float)
+pub fn to_binary(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let value = pop_one_number!(ctx);
+    ctx.stack.push(Object::Symbol {
+        name: format_binary_exponent(&value),
+        auto_eval: false,
+    });
+    Ok(())
+}
+
 /// Bitwise AND: &
 pub fn bitwise_and(ctx: &mut Context) -> Result<()> {
     use rug::Integer;
@@ -1264,3 +1529,316 @@ pub fn bitwise_not(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+/// Highest bit count, the largest word size
+const MAX_BIT_COUNT: u32 = 65536;
+
+/// Pop a bit count (shift amount, bit index or field length) from the stack
+/// The count must be an integer from 0 to MAX_BIT_COUNT
+fn pop_bit_count(ctx: &mut Context) -> Result<u32> {
+    let value = pop_one_number!(ctx);
+    let f = value.to_f64();
+    if !f.is_finite() || f < 0.0 || f > MAX_BIT_COUNT as f64 || f.fract() != 0.0 {
+        return Err(Error::OutOfRange);
+    }
+    Ok(f as u32)
+}
+
+/// Pop an integer operand, returning it with its base
+fn pop_integer(ctx: &mut Context) -> (rug::Integer, u8) {
+    use rug::Integer;
+
+    match ctx.stack.pop().unwrap() {
+        Object::Number { value, base } => (value.to_integer().unwrap_or(Integer::new()), base),
+        _ => unreachable!(),
+    }
+}
+
+/// Two's complement representation of an integer on `width` bits
+fn to_word(value: rug::Integer, width: u32) -> rug::Integer {
+    value.keep_bits(width)
+}
+
+/// Apply a shift-like operation: x n op
+/// The result keeps the base of x
+fn bitwise_with_count(
+    ctx: &mut Context,
+    op: fn(rug::Integer, u32, u32) -> rug::Integer,
+) -> Result<()> {
+    min_arguments!(ctx, 2);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+
+    let count = pop_bit_count(ctx)?;
+    let (int_val, base) = pop_integer(ctx);
+
+    let result_int = op(int_val, count, ctx.config.word_size);
+    let result = Float::with_val(ctx.config.precision_bits, &result_int);
+    push_number!(ctx, result, base);
+    Ok(())
+}
+
+/// Apply a word operation: x op
+/// The result keeps the base of x
+fn bitwise_on_word(ctx: &mut Context, op: fn(rug::Integer, u32) -> rug::Integer) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let (int_val, base) = pop_integer(ctx);
+
+    let result_int = op(int_val, ctx.config.word_size);
+    let result = Float::with_val(ctx.config.precision_bits, &result_int);
+    push_number!(ctx, result, base);
+    Ok(())
+}
+
+/// Apply a bit counting operation: x op
+/// The count is pushed as a decimal number
+fn bitwise_count(ctx: &mut Context, op: fn(rug::Integer, u32) -> u32) -> Result<()> {
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    let (int_val, _base) = pop_integer(ctx);
+
+    let count = op(int_val, ctx.config.word_size);
+    push_number!(ctx, Float::with_val(ctx.config.precision_bits, count));
+    Ok(())
+}
+
+/// Shift left: sl
+/// Stack: x n -> x << n
+pub fn shift_left(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |x, n, _| x << n)
+}
+
+/// Logical shift right: sr
+/// Negative values are taken as two's complement on the word size
+/// Stack: x n -> x >> n
+pub fn shift_right(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |x, n, width| {
+        if x < 0 {
+            to_word(x, width) >> n
+        } else {
+            x >> n
+        }
+    })
+}
+
+/// Arithmetic shift right: asr (sign is preserved)
+/// Stack: x n -> x >> n
+pub fn arithmetic_shift_right(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |x, n, _| x >> n)
+}
+
+/// Rotate left: rl (rotation within the word size)
+/// Stack: x n -> x rotated left by n bits
+pub fn rotate_left(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |x, n, width| {
+        let word = to_word(x, width);
+        let n = n % width;
+        if n == 0 {
+            return word;
+        }
+        let high = word.clone() >> (width - n);
+        to_word(word << n, width) | high
+    })
+}
+
+/// Rotate right: rr (rotation within the word size)
+/// Stack: x n -> x rotated right by n bits
+pub fn rotate_right(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |x, n, width| {
+        let word = to_word(x, width);
+        let n = n % width;
+        if n == 0 {
+            return word;
+        }
+        let low = word.clone() >> n;
+        to_word(word << (width - n), width) | low
+    })
+}
+
+/// Bit set: bset
+/// Stack: x n -> x with bit n set
+pub fn bit_set(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |mut x, n, _| {
+        x.set_bit(n, true);
+        x
+    })
+}
+
+/// Bit clear: bclr
+/// Stack: x n -> x with bit n cleared
+pub fn bit_clear(ctx: &mut Context) -> Result<()> {
+    bitwise_with_count(ctx, |mut x, n, _| {
+        x.set_bit(n, false);
+        x
+    })
+}
+
+/// Bit test: btst
+/// Stack: x n -> 1 if bit n of x is set, 0 otherwise
+pub fn bit_test(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 2);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+
+    let bit = pop_bit_count(ctx)?;
+    let (int_val, _base) = pop_integer(ctx);
+
+    let result = if int_val.get_bit(bit) { 1 } else { 0 };
+    push_number!(ctx, Float::with_val(ctx.config.precision_bits, result));
+    Ok(())
+}
+
+/// Bit field: bfld (extract len bits starting at bit pos)
+/// Stack: x pos len -> field
+pub fn bit_field(ctx: &mut Context) -> Result<()> {
+    min_arguments!(ctx, 3);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+    arg_must_be!(ctx, 2, Number);
+
+    let len = pop_bit_count(ctx)?;
+    let pos = pop_bit_count(ctx)?;
+    let (int_val, base) = pop_integer(ctx);
+
+    let result_int = (int_val >> pos).keep_bits(len);
+    let result = Float::with_val(ctx.config.precision_bits, &result_int);
+    push_number!(ctx, result, base);
+    Ok(())
+}
+
+/// Population count: popcnt (number of bits set within the word size)
+/// Stack: x -> count
+pub fn popcount(ctx: &mut Context) -> Result<()> {
+    bitwise_count(ctx, |x, width| to_word(x, width).count_ones().unwrap_or(0))
+}
+
+/// Count leading zeros: clz (within the word size)
+/// Stack: x -> count
+pub fn count_leading_zeros(ctx: &mut Context) -> Result<()> {
+    bitwise_count(ctx, |x, width| width - to_word(x, width).significant_bits())
+}
+
+/// Count trailing zeros: ctz (word size for zero)
+/// Stack: x -> count
+pub fn count_trailing_zeros(ctx: &mut Context) -> Result<()> {
+    bitwise_count(ctx, |x, width| {
+        to_word(x, width).find_one(0).unwrap_or(width)
+    })
+}
+
+/// Byte swap: bswap (reverse byte order within the word size)
+/// The word size must be a multiple of 8
+/// Stack: x -> x with bytes reversed
+pub fn byte_swap(ctx: &mut Context) -> Result<()> {
+    use rug::Integer;
+
+    min_arguments!(ctx, 1);
+    arg_must_be!(ctx, 0, Number);
+
+    if !ctx.config.word_size.is_multiple_of(8) {
+        return Err(Error::BadValue(format!(
+            "word size {} is not a multiple of 8",
+            ctx.config.word_size
+        )));
+    }
+
+    bitwise_on_word(ctx, |x, width| {
+        let word = to_word(x, width);
+        let mut result = Integer::new();
+        for i in (0..width).step_by(8) {
+            let byte = (word.clone() >> i).keep_bits(8);
+            result |= byte << (width - 8 - i);
+        }
+        result
+    })
+}
+
+/// Bit reversal: brev (reverse bit order within the word size)
+/// Stack: x -> x with bits reversed
+pub fn bit_reverse(ctx: &mut Context) -> Result<()> {
+    use rug::Integer;
+
+    bitwise_on_word(ctx, |x, width| {
+        let word = to_word(x, width);
+        let mut result = Integer::new();
+        for i in 0..width {
+            if word.get_bit(i) {
+                result.set_bit(width - 1 - i, true);
+            }
+        }
+        result
+    })
+}
+
+/// Integer value of the number at `level`, if it is an integer
+fn integer_at(ctx: &Context, level: usize) -> Option<(rug::Integer, u8)> {
+    match ctx.stack.get(level) {
+        Some(Object::Number { value, base }) if value.is_integer() => {
+            value.to_integer().map(|n| (n, *base))
+        }
+        _ => None,
+    }
+}
+
+/// Push an integer reduced modulo `modulus` in [0, modulus)
+fn push_modular(ctx: &mut Context, value: rug::Integer, modulus: &rug::Integer, base: u8) {
+    let mut reduced = value % modulus;
+    if reduced < 0 {
+        reduced += modulus;
+    }
+    let precision = ctx.config.precision_bits.max(modulus.significant_bits());
+    push_number!(ctx, Float::with_val(precision, &reduced), base);
+}
+
+/// In modular mode, apply `op` to the two integers on the stack and reduce the result
+/// Returns Ok(false) with the stack untouched when the mode is off or an operand is not an
+/// integer, and BadValue when `op` has no result (no modular inverse)
+fn modular_binary(
+    ctx: &mut Context,
+    op: fn(rug::Integer, rug::Integer, &rug::Integer) -> Option<rug::Integer>,
+) -> Result<bool> {
+    let modulus = match &ctx.config.modulus {
+        Some(modulus) => modulus.clone(),
+        None => return Ok(false),
+    };
+    let (a, base) = match integer_at(ctx, 1) {
+        Some(a) => a,
+        None => return Ok(false),
+    };
+    let (b, _) = match integer_at(ctx, 0) {
+        Some(b) => b,
+        None => return Ok(false),
+    };
+
+    let result = op(a, b, &modulus)
+        .ok_or_else(|| Error::BadValue(format!("no inverse modulo {}", modulus)))?;
+    ctx.stack.pop();
+    ctx.stack.pop();
+    push_modular(ctx, result, &modulus, base);
+    Ok(true)
+}
+
+/// In modular mode, apply `op` to the integer on the stack and reduce the result
+/// Same return convention as modular_binary
+fn modular_unary(
+    ctx: &mut Context,
+    op: fn(rug::Integer, &rug::Integer) -> Option<rug::Integer>,
+) -> Result<bool> {
+    let modulus = match &ctx.config.modulus {
+        Some(modulus) => modulus.clone(),
+        None => return Ok(false),
+    };
+    let (a, base) = match integer_at(ctx, 0) {
+        Some(a) => a,
+        None => return Ok(false),
+    };
+
+    let result =
+        op(a, &modulus).ok_or_else(|| Error::BadValue(format!("no inverse modulo {}", modulus)))?;
+    ctx.stack.pop();
+    push_modular(ctx, result, &modulus, base);
+    Ok(true)
+}
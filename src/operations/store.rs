@@ -22,9 +22,9 @@ pub fn sto(ctx: &mut Context) -> Result<()> {
     // Pop the value and store it
     let value = ctx.stack.pop().ok_or(Error::MissingOperand)?;
 
-    // Store in local_heap if variable already exists there, otherwise global_heap
-    if ctx.local_heap.contains(&name) {
-        ctx.local_heap.store(name, value);
+    // Store in the innermost local frame holding the variable, otherwise global_heap
+    if let Some(local) = ctx.find_local_mut(&name) {
+        *local = value;
     } else {
         ctx.global_heap.store(name, value);
     }
@@ -92,7 +92,7 @@ pub fn sto_add(ctx: &mut Context) -> Result<()> {
         _ => unreachable!(),
     };
 
-    if !ctx.global_heap.contains(&name) && !ctx.local_heap.contains(&name) {
+    if ctx.find_variable(&name).is_none() {
         return Err(Error::UnknownVariable(name));
     }
 
@@ -121,7 +121,7 @@ pub fn sto_sub(ctx: &mut Context) -> Result<()> {
         _ => unreachable!(),
     };
 
-    if !ctx.global_heap.contains(&name) && !ctx.local_heap.contains(&name) {
+    if ctx.find_variable(&name).is_none() {
         return Err(Error::UnknownVariable(name));
     }
 
@@ -148,7 +148,7 @@ pub fn sto_mul(ctx: &mut Context) -> Result<()> {
         _ => unreachable!(),
     };
 
-    if !ctx.global_heap.contains(&name) && !ctx.local_heap.contains(&name) {
+    if ctx.find_variable(&name).is_none() {
         return Err(Error::UnknownVariable(name));
     }
 
@@ -175,7 +175,7 @@ pub fn sto_div(ctx: &mut Context) -> Result<()> {
         _ => unreachable!(),
     };
 
-    if !ctx.global_heap.contains(&name) && !ctx.local_heap.contains(&name) {
+    if ctx.find_variable(&name).is_none() {
         return Err(Error::UnknownVariable(name));
     }
 
@@ -203,7 +203,7 @@ pub fn sto_neg(ctx: &mut Context) -> Result<()> {
         _ => unreachable!(),
     };
 
-    if !ctx.global_heap.contains(&name) && !ctx.local_heap.contains(&name) {
+    if ctx.find_variable(&name).is_none() {
         return Err(Error::UnknownVariable(name));
     }
 
@@ -229,7 +229,7 @@ pub fn sto_inv(ctx: &mut Context) -> Result<()> {
         _ => unreachable!(),
     };
 
-    if !ctx.global_heap.contains(&name) && !ctx.local_heap.contains(&name) {
+    if ctx.find_variable(&name).is_none() {
         return Err(Error::UnknownVariable(name));
     }
 
@@ -253,13 +253,21 @@ pub fn vars(ctx: &mut Context) -> Result<()> {
         }
     }
 
-    if !ctx.local_heap.is_empty() {
-        println!("\nLocal variables:");
-        for name in ctx.local_heap.var_names() {
-            if let Some(obj) = ctx.local_heap.recall(name) {
+    // Local variables visible from here, innermost frame first
+    let mut shown: Vec<&String> = Vec::new();
+    for frame in ctx.visible_frames().iter().rev() {
+        for name in frame.var_names() {
+            if shown.contains(&name) {
+                continue;
+            }
+            if shown.is_empty() {
+                println!("\nLocal variables:");
+            }
+            if let Some(obj) = frame.recall(name) {
                 println!("  var {}: name '{}', type {}", index, name, obj.type_name());
                 index += 1;
             }
+            shown.push(name);
         }
     }
 
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: GPL-3.0-only
+// Sexagesimal operations: hours-minutes-seconds and degrees-minutes-seconds
+
+use crate::context::Context;
+use crate::error::Result;
+use rug::Float;
+
+/// Extra bits used during conversions so that the final rounding hides truncation errors
+const GUARD_BITS: u32 = 32;
+
+/// Round a value to the nearest integer when it is within a few ulps of the `precision`
+/// input, so that 1.3 stored as 1.2999... still reads as 1 hour 30 minutes
+fn snap(value: Float, precision: u32) -> Float {
+    let nearest = value.clone().round();
+    let tolerance = Float::with_val(value.prec(), value.abs_ref()) >> precision.saturating_sub(8);
+    if Float::with_val(value.prec(), &value - &nearest).abs() <= tolerance {
+        nearest
+    } else {
+        value
+    }
+}
+
+/// Convert a decimal value to H.MMSS format
+fn decimal_to_hms(value: &Float, precision: u32) -> Float {
+    if !value.is_finite() {
+        return Float::with_val(precision, value);
+    }
+    let work = precision + GUARD_BITS;
+    let abs_val = snap(Float::with_val(work, value.abs_ref()), precision);
+
+    let hours = abs_val.clone().trunc();
+    let total_minutes = snap(Float::with_val(work, &abs_val - &hours) * 60u32, precision);
+    let minutes = total_minutes.clone().trunc();
+    let seconds = Float::with_val(work, &total_minutes - &minutes) * 60u32;
+
+    let hms = hours + minutes / 100u32 + seconds / 10000u32;
+    let result = Float::with_val(precision, &hms);
+    if value.is_sign_negative() {
+        -result
+    } else {
+        result
+    }
+}
+
+/// Convert a value in H.MMSS format to decimal
+fn hms_to_decimal(value: &Float, precision: u32) -> Float {
+    if !value.is_finite() {
+        return Float::with_val(precision, value);
+    }
+    let work = precision + GUARD_BITS;
+    let abs_val = snap(Float::with_val(work, value.abs_ref()), precision);
+
+    let hours = abs_val.clone().trunc();
+    let mmss = snap(Float::with_val(work, &abs_val - &hours) * 100u32, precision);
+    let minutes = mmss.clone().trunc();
+    let seconds = Float::with_val(work, &mmss - &minutes) * 100u32;
+
+    let decimal = hours + minutes / 60u32 + seconds / 3600u32;
+    let result = Float::with_val(precision, &decimal);
+    if value.is_sign_negative() {
+        -result
+    } else {
+        result
+    }
+}
+
+/// Apply an operation on two H.MMSS values, computed on their decimal equivalents
+fn hms_binary(ctx: &mut Context, op: fn(Float, Float) -> Float) -> Result<()> {
+    min_arguments!(ctx, 2);
+    arg_must_be!(ctx, 0, Number);
+    arg_must_be!(ctx, 1, Number);
+
+    let precision = ctx.config.precision_bits;
+    let b = pop_one_number!(ctx);
+    let a = pop_one_number!(ctx);
+    let result = op(hms_to_decimal(&a, precision), hms_to_decimal(&b, precision));
+    push_number!(ctx, decimal_to_hms(&result, precision));
+    Ok(())
+}
+
+/// ->HMS: ->hms (convert decimal hours or degrees to H.MMSS format)
+/// Stack: decimal -> H.MMSS
+pub fn to_hms(ctx: &mut Context) -> Result<()> {
+    let value = pop_one_number!(ctx);
+    push_number!(ctx, decimal_to_hms(&value, ctx.config.precision_bits));
+    Ok(())
+}
+
+/// HMS->: hms-> (convert H.MMSS format to decimal hours or degrees)
+/// Stack: H.MMSS -> decimal
+pub fn from_hms(ctx: &mut Context) -> Result<()> {
+    let value = pop_one_number!(ctx);
+    push_number!(ctx, hms_to_decimal(&value, ctx.config.precision_bits));
+    Ok(())
+}
+
+/// HMS+: hms+ (add two values in H.MMSS format)
+/// Stack: H.MMSS H.MMSS -> H.MMSS
+pub fn hms_add(ctx: &mut Context) -> Result<()> {
+    hms_binary(ctx, |a, b| a + b)
+}
+
+/// HMS-: hms- (subtract two values in H.MMSS format)
+/// Stack: H.MMSS H.MMSS -> H.MMSS
+pub fn hms_sub(ctx: &mut Context) -> Result<()> {
+    hms_binary(ctx, |a, b| a - b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_to_hms() {
+        let hms = decimal_to_hms(&Float::with_val(128, 1.5), 128);
+        assert_eq!(hms, Float::with_val(128, Float::parse("1.3").unwrap()));
+
+        let hms = decimal_to_hms(&Float::with_val(128, -2.75), 128);
+        assert_eq!(hms, Float::with_val(128, Float::parse("-2.45").unwrap()));
+    }
+
+    #[test]
+    fn test_hms_roundtrip() {
+        let hms = Float::with_val(128, Float::parse("12.345678").unwrap());
+        let decimal = hms_to_decimal(&hms, 128);
+        assert_eq!(decimal_to_hms(&decimal, 128), hms);
+    }
+}
@@ -3,11 +3,97 @@
 
 use crate::context::Context;
 use crate::error::Result;
+use crate::number::{rounded, AngleMode};
 use crate::object::Object;
-use rug::float::Constant;
-use rug::Float;
+use crate::operations::complex::push_out_of_domain;
+use rug::float::{Constant, Round};
+use rug::{Complex, Float};
+
+/// Sine of an angle expressed in `mode` units
+pub(crate) fn sin_angle(mut value: Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => value.sin_u_round(units, round),
+        None => value.sin_round(round),
+    };
+    value
+}
+
+/// Cosine of an angle expressed in `mode` units
+pub(crate) fn cos_angle(mut value: Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => value.cos_u_round(units, round),
+        None => value.cos_round(round),
+    };
+    value
+}
+
+/// Tangent of an angle expressed in `mode` units
+fn tan_angle(mut value: Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => value.tan_u_round(units, round),
+        None => value.tan_round(round),
+    };
+    value
+}
+
+/// Arcsine returning an angle in `mode` units
+fn asin_angle(mut value: Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => value.asin_u_round(units, round),
+        None => value.asin_round(round),
+    };
+    value
+}
+
+/// Arccosine returning an angle in `mode` units
+fn acos_angle(mut value: Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => value.acos_u_round(units, round),
+        None => value.acos_round(round),
+    };
+    value
+}
+
+/// Arctangent returning an angle in `mode` units
+fn atan_angle(mut value: Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => value.atan_u_round(units, round),
+        None => value.atan_round(round),
+    };
+    value
+}
+
+/// Two-argument arctangent of y/x returning an angle in `mode` units
+pub(crate) fn atan2_angle(mut y: Float, x: &Float, mode: AngleMode, round: Round) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => y.atan2_u_round(x, units, round),
+        None => y.atan2_round(x, round),
+    };
+    y
+}
+
+/// Half a turn (pi, 180 or 200) in `mode` units
+pub(crate) fn half_turn(precision: u32, mode: AngleMode) -> Float {
+    match mode.units_per_turn() {
+        Some(units) => Float::with_val(precision, units / 2),
+        None => Float::with_val(precision, Constant::Pi),
+    }
+}
+
+/// A complex angle computed in radians, its real part or, if `imaginary`, its imaginary part
+/// converted to `mode` units
+fn complex_angle(z: Complex, imaginary: bool, mode: AngleMode) -> Complex {
+    let (mut re, mut im) = z.into_real_imag();
+    let angle = if imaginary { &mut im } else { &mut re };
+    if mode.units_per_turn().is_some() {
+        let precision = angle.prec();
+        *angle *= half_turn(precision, mode);
+        *angle /= Float::with_val(precision, Constant::Pi);
+    }
+    Complex::with_val((re.prec(), im.prec()), (re, im))
+}
 
-/// SIN: sin (sine function, input in radians)
+/// SIN: sin (sine function, input in the current angle mode)
 pub fn sin(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
@@ -18,7 +104,7 @@ pub fn sin(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.sin();
+            let result = sin_angle(value, ctx.config.angle_mode, ctx.config.rounding);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -39,7 +125,7 @@ pub fn sin(ctx: &mut Context) -> Result<()> {
     }
 }
 
-/// COS: cos (cosine function, input in radians)
+/// COS: cos (cosine function, input in the current angle mode)
 pub fn cos(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
@@ -50,7 +136,7 @@ pub fn cos(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.cos();
+            let result = cos_angle(value, ctx.config.angle_mode, ctx.config.rounding);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -71,7 +157,7 @@ pub fn cos(ctx: &mut Context) -> Result<()> {
     }
 }
 
-/// TAN: tan (tangent function, input in radians)
+/// TAN: tan (tangent function, input in the current angle mode)
 pub fn tan(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
@@ -82,7 +168,7 @@ pub fn tan(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.tan();
+            let result = tan_angle(value, ctx.config.angle_mode, ctx.config.rounding);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -103,7 +189,7 @@ pub fn tan(ctx: &mut Context) -> Result<()> {
     }
 }
 
-/// ASIN: asin (arcsine function, returns radians)
+/// ASIN: asin (arcsine function, returns an angle in the current angle mode)
 pub fn asin(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
@@ -114,8 +200,13 @@ pub fn asin(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.asin();
-            push_number!(ctx, result, base);
+            if *value.as_abs() > 1 {
+                let mode = ctx.config.angle_mode;
+                push_out_of_domain(ctx, value, base, |z| complex_angle(z.asin(), false, mode));
+            } else {
+                let result = asin_angle(value, ctx.config.angle_mode, ctx.config.rounding);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -135,7 +226,7 @@ pub fn asin(ctx: &mut Context) -> Result<()> {
     }
 }
 
-/// ACOS: acos (arccosine function, returns radians)
+/// ACOS: acos (arccosine function, returns an angle in the current angle mode)
 pub fn acos(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
@@ -146,8 +237,13 @@ pub fn acos(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.acos();
-            push_number!(ctx, result, base);
+            if *value.as_abs() > 1 {
+                let mode = ctx.config.angle_mode;
+                push_out_of_domain(ctx, value, base, |z| complex_angle(z.acos(), false, mode));
+            } else {
+                let result = acos_angle(value, ctx.config.angle_mode, ctx.config.rounding);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -167,7 +263,7 @@ pub fn acos(ctx: &mut Context) -> Result<()> {
     }
 }
 
-/// ATAN: atan (arctangent function, returns radians)
+/// ATAN: atan (arctangent function, returns an angle in the current angle mode)
 pub fn atan(ctx: &mut Context) -> Result<()> {
     min_arguments!(ctx, 1);
 
@@ -178,7 +274,7 @@ pub fn atan(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.atan();
+            let result = atan_angle(value, ctx.config.angle_mode, ctx.config.rounding);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -256,7 +352,7 @@ pub fn atan2(ctx: &mut Context) -> Result<()> {
     };
 
     let (x, y) = pop_two_numbers!(ctx);
-    let result = y.atan2(&x);
+    let result = atan2_angle(y, &x, ctx.config.angle_mode, ctx.config.rounding);
     push_number!(ctx, result, base);
     Ok(())
 }
@@ -272,7 +368,7 @@ pub fn sinh(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.sinh();
+            let result = rounded(value, ctx.config.rounding, Float::sinh_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -304,7 +400,7 @@ pub fn cosh(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.cosh();
+            let result = rounded(value, ctx.config.rounding, Float::cosh_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -336,7 +432,7 @@ pub fn tanh(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.tanh();
+            let result = rounded(value, ctx.config.rounding, Float::tanh_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -368,7 +464,7 @@ pub fn asinh(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.asinh();
+            let result = rounded(value, ctx.config.rounding, Float::asinh_round);
             push_number!(ctx, result, base);
             Ok(())
         }
@@ -400,8 +496,14 @@ pub fn acosh(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.acosh();
-            push_number!(ctx, result, base);
+            if value < 1 {
+                // The imaginary part is an angle
+                let mode = ctx.config.angle_mode;
+                push_out_of_domain(ctx, value, base, |z| complex_angle(z.acosh(), true, mode));
+            } else {
+                let result = rounded(value, ctx.config.rounding, Float::acosh_round);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -432,8 +534,14 @@ pub fn atanh(ctx: &mut Context) -> Result<()> {
                 Object::Number { value, base } => (value, base),
                 _ => unreachable!(),
             };
-            let result = value.atanh();
-            push_number!(ctx, result, base);
+            if *value.as_abs() > 1 {
+                // The imaginary part is an angle
+                let mode = ctx.config.angle_mode;
+                push_out_of_domain(ctx, value, base, |z| complex_angle(z.atanh(), true, mode));
+            } else {
+                let result = rounded(value, ctx.config.rounding, Float::atanh_round);
+                push_number!(ctx, result, base);
+            }
             Ok(())
         }
         Some(Object::Complex { .. }) => {
@@ -515,6 +623,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_deg_mode() {
+        let mut ctx = Context::new();
+        ctx.config.angle_mode = AngleMode::Deg;
+
+        // sin(30°) = 0.5 exactly
+        push_number!(ctx, Float::with_val(128, 30));
+        sin(&mut ctx).unwrap();
+        match ctx.stack.get(0).unwrap() {
+            Object::Number { value, .. } => assert_eq!(*value, 0.5),
+            _ => panic!("Expected number"),
+        }
+        ctx.stack.pop();
+
+        // atan(1) = 45°
+        push_number!(ctx, Float::with_val(128, 1));
+        atan(&mut ctx).unwrap();
+        match ctx.stack.get(0).unwrap() {
+            Object::Number { value, .. } => assert_eq!(*value, 45),
+            _ => panic!("Expected number"),
+        }
+    }
+
     #[test]
     fn test_sinh() {
         let mut ctx = Context::new();
@@ -2,10 +2,14 @@
 // Parser for converting tokens to objects
 
 use crate::error::{Error, Result};
-use crate::lexer::Token;
-use crate::object::{BranchArgs, Object};
+use crate::lexer::{Lexer, Span, Token};
+use crate::number::NumberConfig;
+use crate::object::{BranchArgs, Object, Program};
 use crate::operations;
 
+/// Objects with their span in their source
+type Spanned = (Vec<Object>, Vec<Span>);
+
 /// Parser converts tokens to objects
 pub struct Parser;
 
@@ -13,27 +17,59 @@ impl Parser {
     /// Build objects from tokens
     /// This is the main parsing function that converts lexer output to executable objects
     pub fn build_objects(tokens: Vec<Token>) -> Result<Vec<Object>> {
+        Self::build_objects_with_config(tokens, &NumberConfig::default())
+    }
+
+    /// Build objects from tokens, numbers in nested programs being read with `config`
+    /// precision and input base
+    pub fn build_objects_with_config(
+        tokens: Vec<Token>,
+        config: &NumberConfig,
+    ) -> Result<Vec<Object>> {
+        let tokens = tokens
+            .into_iter()
+            .map(|token| (token, Span::default()))
+            .collect();
+        Ok(Self::build_objects_with_spans(tokens, config)?.0)
+    }
+
+    /// Build objects from tokens and their spans, returning the span of each object
+    /// The objects of nested programs keep their spans in the program source
+    pub fn build_objects_with_spans(
+        tokens: Vec<(Token, Span)>,
+        config: &NumberConfig,
+    ) -> Result<Spanned> {
         let mut objects = Vec::new();
+        let mut spans = Vec::new();
 
-        for token in tokens {
-            let obj = Self::token_to_object(token)?;
+        for (token, span) in tokens {
+            let obj = Self::token_to_object(token, config)?;
             objects.push(obj);
+            spans.push(span);
         }
 
-        Ok(objects)
+        Ok((objects, spans))
     }
 
     /// Extract local variable declarations from program tokens
     /// Pattern: [prefix_code...] -> var1 var2 ... << body >>
-    /// Returns (prefix_objects, variable_names, body_objects)
-    fn extract_local_vars(tokens: Vec<Token>) -> Result<(Vec<Object>, Vec<String>, Vec<Object>)> {
+    /// Returns (prefix_objects, variable_names, body_objects), with their spans in `source`
+    fn extract_local_vars(
+        tokens: Vec<(Token, Span)>,
+        source: &str,
+        config: &NumberConfig,
+    ) -> Result<(Spanned, Vec<String>, Spanned)> {
         if tokens.is_empty() {
-            return Ok((Vec::new(), Vec::new(), Vec::new()));
+            return Ok((
+                (Vec::new(), Vec::new()),
+                Vec::new(),
+                (Vec::new(), Vec::new()),
+            ));
         }
 
         // Look for '->' anywhere in the token list
         let mut arrow_index = None;
-        for (i, token) in tokens.iter().enumerate() {
+        for (i, (token, _)) in tokens.iter().enumerate() {
             if let Token::Symbol {
                 name,
                 quoted: false,
@@ -53,19 +89,19 @@ impl Parser {
         if let Some(arrow_idx) = arrow_index {
             // Found local variable declaration
             let mut var_names = Vec::new();
-            let mut prefix_objects = Vec::new();
+            let mut prefix = (Vec::new(), Vec::new());
 
             // Parse everything before -> as prefix code
             if arrow_idx > 0 {
                 let prefix_tokens = tokens[..arrow_idx].to_vec();
-                prefix_objects = Self::build_objects(prefix_tokens)?;
+                prefix = Self::build_objects_with_spans(prefix_tokens, config)?;
             }
 
             // Check if arrow token has embedded variable name (tight spacing)
             let first_var_name = if let Token::Symbol {
                 name,
                 quoted: false,
-            } = &tokens[arrow_idx]
+            } = &tokens[arrow_idx].0
             {
                 if name.starts_with("->") && name.len() > 2 {
                     Some(name[2..].to_string())
@@ -85,7 +121,7 @@ impl Parser {
 
             // Collect variable names until we hit a Program token
             while i < tokens.len() {
-                match &tokens[i] {
+                match &tokens[i].0 {
                     Token::Symbol {
                         name,
                         quoted: false,
@@ -101,49 +137,64 @@ impl Parser {
                         // Not a valid local variable declaration
                         // The -> was probably part of a keyword like ->str
                         // Fall through to parse normally
-                        let objects = Self::build_objects(tokens)?;
-                        return Ok((Vec::new(), Vec::new(), objects));
+                        let objects = Self::build_objects_with_spans(tokens, config)?;
+                        return Ok(((Vec::new(), Vec::new()), Vec::new(), objects));
                     }
                 }
             }
 
             // The next token should be the Program (the body)
             if i < tokens.len() {
-                if let Token::Program(content) = &tokens[i] {
+                if let (Token::Program(content), span) = &tokens[i] {
                     // Parse the inner program (body)
                     // The body might itself have a -> pattern, so we recursively check
-                    use crate::lexer::Lexer;
-                    let inner_tokens = Lexer::analyze(content)?;
+                    let inner_tokens = Lexer::analyze_with_spans(content, config)?;
                     let (inner_prefix, inner_vars, inner_body) =
-                        Self::extract_local_vars(inner_tokens)?;
+                        Self::extract_local_vars(inner_tokens, content, config)?;
 
                     let mut body_objects = Vec::new();
+                    let mut body_spans = Vec::new();
 
                     // If the body has its own local vars, create a nested Program object
-                    if !inner_vars.is_empty() || !inner_prefix.is_empty() {
-                        use crate::object::Program;
+                    if !inner_vars.is_empty() || !inner_prefix.0.is_empty() {
                         let nested_program = Object::Program(Box::new(Program {
-                            objects: inner_body,
+                            objects: inner_body.0,
+                            spans: inner_body.1,
                             value: content.clone(),
                             local_vars: inner_vars,
-                            prefix_objects: inner_prefix,
+                            prefix_objects: inner_prefix.0,
+                            prefix_spans: inner_prefix.1,
                             auto_eval: true, // Nested programs with local vars should auto-eval
                         }));
                         body_objects.push(nested_program);
+                        body_spans.push(*span);
                     } else {
-                        // No nested local vars, just use the body objects directly
-                        body_objects.extend(inner_body);
+                        // No nested local vars, just use the body objects directly, their spans
+                        // moved from the body to this program source
+                        let delimiter = if source.chars().nth(span.start) == Some('«') {
+                            1
+                        } else {
+                            2
+                        };
+                        let offset = span.start + delimiter;
+                        body_objects.extend(inner_body.0);
+                        body_spans.extend(inner_body.1.into_iter().map(|body_span| Span {
+                            start: body_span.start + offset,
+                            end: body_span.end + offset,
+                        }));
                     }
 
                     // Process remaining tokens after the Program (e.g., eval, other commands)
                     i += 1;
                     if i < tokens.len() {
                         let remaining_tokens = tokens[i..].to_vec();
-                        let remaining_objects = Self::build_objects(remaining_tokens)?;
+                        let (remaining_objects, remaining_spans) =
+                            Self::build_objects_with_spans(remaining_tokens, config)?;
                         body_objects.extend(remaining_objects);
+                        body_spans.extend(remaining_spans);
                     }
 
-                    return Ok((prefix_objects, var_names, body_objects));
+                    return Ok((prefix, var_names, (body_objects, body_spans)));
                 }
             }
 
@@ -153,12 +204,12 @@ impl Parser {
         }
 
         // No local variable declaration (or false positive), parse normally
-        let objects = Self::build_objects(tokens)?;
-        Ok((Vec::new(), Vec::new(), objects))
+        let objects = Self::build_objects_with_spans(tokens, config)?;
+        Ok(((Vec::new(), Vec::new()), Vec::new(), objects))
     }
 
     /// Convert a single token to an object
-    fn token_to_object(token: Token) -> Result<Object> {
+    fn token_to_object(token: Token, config: &NumberConfig) -> Result<Object> {
         match token {
             Token::Number { value, base } => Ok(Object::Number { value, base }),
 
@@ -169,7 +220,7 @@ impl Parser {
                 im_base,
             } => {
                 use rug::Complex;
-                let value = Complex::with_val(128, (re, im));
+                let value = Complex::with_val(config.precision_bits, (re, im));
                 Ok(Object::Complex {
                     value,
                     re_base,
@@ -212,23 +263,23 @@ impl Parser {
 
             Token::Program(content) => {
                 // Recursively parse the program content
-                use crate::lexer::Lexer;
-                let tokens = Lexer::analyze(&content)?;
+                let tokens = Lexer::analyze_with_spans(&content, config)?;
 
                 // Check for local variable declaration: -> var1 var2 ... << body >>
-                let (mut prefix_objects, local_vars, mut body_objects) =
-                    Self::extract_local_vars(tokens)?;
+                let ((mut prefix_objects, prefix_spans), local_vars, (mut body_objects, spans)) =
+                    Self::extract_local_vars(tokens, &content, config)?;
 
                 // Link control flow for both prefix and body objects
                 crate::program::link_control_flow(&mut prefix_objects)?;
                 crate::program::link_control_flow(&mut body_objects)?;
 
-                use crate::object::Program;
                 Ok(Object::Program(Box::new(Program {
                     objects: body_objects,
+                    spans,
                     value: content,
                     local_vars,
                     prefix_objects,
+                    prefix_spans,
                     auto_eval: false, // Top-level programs don't auto-eval
                 })))
             }
@@ -287,4 +338,31 @@ mod tests {
             _ => panic!("Expected Number"),
         }
     }
+
+    #[test]
+    fn test_parse_spans() {
+        let config = NumberConfig::default();
+        let source = "1 << -> n << n sin >> >>";
+        let tokens = Lexer::analyze_with_spans(source, &config).unwrap();
+        let (objects, spans) = Parser::build_objects_with_spans(tokens, &config).unwrap();
+        assert_eq!(
+            spans,
+            vec![Span { start: 0, end: 1 }, Span { start: 2, end: 24 }]
+        );
+
+        // Program spans are positions in the program source
+        match &objects[1] {
+            Object::Program(program) => {
+                assert_eq!(program.local_vars, vec!["n".to_string()]);
+                assert_eq!(
+                    program.spans,
+                    vec![Span { start: 9, end: 10 }, Span { start: 11, end: 14 }]
+                );
+                let chars: Vec<char> = program.value.chars().collect();
+                let sin: String = chars[11..14].iter().collect();
+                assert_eq!(sin, "sin");
+            }
+            _ => panic!("Expected Program"),
+        }
+    }
 }
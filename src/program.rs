@@ -3,335 +3,1102 @@
 
 use crate::context::Context;
 use crate::error::{Error, Result};
-use crate::lexer::Lexer;
-use crate::object::Object;
+use crate::lexer::{Lexer, Span};
+use crate::object::{Object, Program};
 use crate::parser::Parser;
+use crate::stack::Heap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-/// Execute a program string
+/// Keywords acting outside of the context or giving a different result on each run, lines
+/// running them are not re-run by verified display
+const NOT_REPEATABLE: &[&str] = &[
+    "help", "h", "?", "history", "version", "uname", "test", "quit", "q", "exit", "edit", "halt",
+    "cont", "sst", "sst↓", "kill", "trace", "profile", "tevel", "teval", "date", "time", "ticks",
+];
+
+/// Number of precision doublings tried by verified display
+const VERIFY_RUNS: u32 = 4;
+
+/// Highest precision in bits, as accepted by prec
+const MAX_PRECISION_BITS: u32 = 1_000_000;
+
+/// Number of stack values shown by trace
+const TRACE_VALUES: usize = 3;
+
+/// Highest number of program runs inside each other, each one taking room on the native stack
+/// whatever `maxdepth`
+const MAX_NESTED_RUNS: usize = 256;
+
+/// Longest source line shown by error reports, longer lines are cut around the failing object
+const REPORT_WIDTH: usize = 72;
+
+/// Set by Ctrl-C while a line is executed, the running program is aborted at its next instruction
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// SIGINT handler installed while a line is executed, the previous one is restored on drop
+struct InterruptGuard {
+    previous: libc::sighandler_t,
+}
+
+impl InterruptGuard {
+    fn install() -> Self {
+        let handler = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        let previous = unsafe { libc::signal(libc::SIGINT, handler) };
+        if previous != handler {
+            // Outermost execution: forget an interruption left from a previous line
+            INTERRUPTED.store(false, Ordering::SeqCst);
+        }
+        Self { previous }
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        // SAFETY: restores the handler returned by signal() in install()
+        unsafe {
+            libc::signal(libc::SIGINT, self.previous);
+        }
+    }
+}
+
+/// Execute a program string, its numbers being read with the current precision and input base
 pub fn execute(source: &str, ctx: &mut Context) -> Result<()> {
+    ctx.clear_error_context();
+
     // Lexical analysis
-    let tokens = Lexer::analyze(source)?;
+    let tokens = Lexer::analyze_with_spans(source, &ctx.config)?;
 
     // Parse to objects
-    let mut objects = Parser::build_objects(tokens)?;
+    let (mut objects, spans) = Parser::build_objects_with_spans(tokens, &ctx.config)?;
 
     // Preprocess: link control flow
     link_control_flow(&mut objects)?;
 
-    // Execute
-    run_objects(&objects, ctx)
+    // Execute, Ctrl-C aborting the line instead of killing rpnx
+    let _guard = InterruptGuard::install();
+    run_source(objects, spans, source.to_string(), ctx)
 }
 
-/// Execute a vector of objects
-pub fn run_objects(objects: &[Object], ctx: &mut Context) -> Result<()> {
-    use crate::operations::branch::{RT_ERROR, STEP_OUT};
+/// Context before a line is run, for its verified display: None when verified display is off
+pub fn before_line(ctx: &mut Context) -> Option<Context> {
+    if !ctx.config.verified {
+        return None;
+    }
+    ctx.not_repeatable = false;
+    Some(ctx.clone())
+}
 
-    // Make a mutable copy of objects for updating branch args during execution
-    let mut objects = objects.to_vec();
-    let mut ip = 0; // Instruction pointer
+/// Display the stack of `after`, top first, once `source` has turned `before` into `after`
+/// The line is re-run from `before` at twice, four times... the precision until the displayed
+/// values stop changing. Digits still changing in the last run are preceded by a `~`
+pub fn verified_display(source: &str, before: &Context, after: &Context) -> Vec<String> {
+    let display = |ctx: &Context| -> Vec<String> {
+        ctx.stack
+            .iter()
+            .map(|obj| obj.display(&after.config))
+            .collect()
+    };
+
+    let mut last = display(after);
+    if after.not_repeatable {
+        return last;
+    }
 
-    while ip < objects.len() {
-        // Clone necessary data to avoid borrow conflicts
-        let obj = objects[ip].clone();
+    let mut previous = None;
+    let mut precision = before.config.precision_bits.max(128);
+    for _ in 0..VERIFY_RUNS {
+        precision = (precision * 2).min(MAX_PRECISION_BITS);
+        let mut trial = before.clone();
+        trial.config.precision_bits = precision;
+        if execute(source, &mut trial).is_err() {
+            break;
+        }
+        let current = display(&trial);
+        if current == last {
+            return current;
+        }
+        previous = Some(std::mem::replace(&mut last, current));
+    }
 
-        match obj {
-            // Keyword: call handler function
-            Object::Keyword { handler, .. } => {
-                handler(ctx)?;
-                ip += 1;
+    // Not stable: mark where each value starts to differ from the previous run
+    let Some(previous) = previous else {
+        return last;
+    };
+    last.into_iter()
+        .enumerate()
+        .map(|(i, value)| match previous.get(i) {
+            Some(other) if *other == value => value,
+            other => {
+                let stable: usize = value
+                    .chars()
+                    .zip(other.map(|o| o.chars()).into_iter().flatten())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a.len_utf8())
+                    .sum();
+                format!("{}~{}", &value[..stable], &value[stable..])
             }
+        })
+        .collect()
+}
 
-            // Branch: call branch handler (control flow)
-            Object::Branch {
-                handler,
-                args,
-                name,
-            } => {
-                let mut args_mut = args;
-
-                // Special handling for ELSE: copy condition from IF (which was set by THEN)
-                if name == "else" {
-                    let if_index = args.arg3;
-                    if let Some(Object::Branch { args: if_args, .. }) = objects.get(if_index) {
-                        args_mut.condition = if_args.condition;
-                    }
-                }
+/// A program run by the evaluator
+#[derive(Debug, Clone)]
+struct Frame {
+    /// Copy of the objects, for updating branch args during execution
+    objects: Vec<Object>,
 
-                // Special handling for NEXT/STEP: copy loop bounds from FOR/START on first iteration
-                if (name == "next" || name == "step") && !args_mut.arg_bool {
-                    if let Some(Object::Branch { args: for_args, .. }) = objects.get(args_mut.arg1)
-                    {
-                        args_mut.first_index = for_args.first_index;
-                        args_mut.last_index = for_args.last_index;
-                    }
-                }
+    /// Position of each object in the source, for error reports
+    spans: Vec<Span>,
 
-                let next_ip = handler(ctx, &mut args_mut)?;
+    /// Source of the command line or program, empty if unknown
+    source: String,
 
-                // Special handling for THEN: store condition back to IF so ELSE can read it
-                if name == "then" {
-                    let if_index = args.arg3;
-                    if let Some(Object::Branch { args: if_args, .. }) = objects.get_mut(if_index) {
-                        if_args.condition = args_mut.condition;
-                    }
-                }
+    /// Instruction pointer
+    ip: usize,
 
-                // Special handling for FOR: initialize loop variable
-                if name == "for" && args_mut.arg1 != STEP_OUT {
-                    // Get loop variable symbol
-                    if let Some(Object::Symbol { name: var_name, .. }) = objects.get(args_mut.arg1)
-                    {
-                        // Store initial value in local heap
-                        use rug::Float;
-                        let initial_value = Object::Number {
-                            value: Float::with_val(ctx.config.precision_bits, args_mut.first_index),
-                            base: 10,
-                        };
-                        ctx.local_heap.store(var_name.clone(), initial_value);
-                    }
-                }
+    /// Error traps of the IFERR clauses being run: (IFERR index, error clause address,
+    /// local frames depth at IFERR)
+    traps: Vec<(usize, usize, usize)>,
 
-                // Special handling for NEXT/STEP: update loop variable
-                if (name == "next" || name == "step") && args_mut.arg1 != STEP_OUT {
-                    // Get FOR/START index
-                    if let Some(Object::Branch { args: for_args, .. }) = objects.get(args_mut.arg1)
-                    {
-                        if for_args.arg1 != STEP_OUT {
-                            // This is a FOR loop (not START)
-                            if let Some(Object::Symbol { name: var_name, .. }) =
-                                objects.get(for_args.arg1)
-                            {
-                                // Update loop variable in local heap
-                                // For STEP: compute exact value to avoid accumulation errors
-                                // For NEXT: compute from iteration count
-                                use rug::Float;
-                                let new_value = if name == "step" {
-                                    // STEP: Compute with MPFR precision from stored step value
-                                    let step_key = format!("__step_{}", args_mut.arg1);
-                                    if let Some(Object::Number {
-                                        value: step_mpfr, ..
-                                    }) = ctx.local_heap.recall(&step_key)
-                                    {
-                                        // Compute: start + (iteration * step) using MPFR
-                                        let start = Float::with_val(
-                                            ctx.config.precision_bits,
-                                            args_mut.first_index,
-                                        );
-                                        let iteration = Float::with_val(
-                                            ctx.config.precision_bits,
-                                            args_mut.iteration_count,
-                                        );
-                                        let current = start + (iteration * step_mpfr);
-                                        Object::Number {
-                                            value: current,
-                                            base: 10,
-                                        }
-                                    } else {
-                                        // Fallback (shouldn't happen)
-                                        let current_value = args_mut.first_index
-                                            + (args_mut.iteration_count as f64
-                                                * args_mut.step_value);
-                                        Object::Number {
-                                            value: Float::with_val(
-                                                ctx.config.precision_bits,
-                                                current_value,
-                                            ),
-                                            base: 10,
-                                        }
-                                    }
-                                } else {
-                                    // NEXT: simple increment by 1
-                                    let current_value =
-                                        args_mut.first_index + (args_mut.iteration_count as f64);
-                                    Object::Number {
-                                        value: Float::with_val(
-                                            ctx.config.precision_bits,
-                                            current_value,
-                                        ),
-                                        base: 10,
-                                    }
-                                };
-                                ctx.local_heap.store(var_name.clone(), new_value);
-                            }
-                        }
-                    }
-                }
+    /// Local frames depth to restore when the frame ends, dropping its local variables
+    base_depth: usize,
 
-                // Update args in objects array (for stateful branches like FOR/NEXT)
-                if let Object::Branch { args: obj_args, .. } = &mut objects[ip] {
-                    *obj_args = args_mut;
-                }
+    /// Program whose local variables are bound, and body run, when this prefix code ends
+    then_bind: Option<Box<Program>>,
 
-                match next_ip {
-                    STEP_OUT => {
-                        // Clean up loop variable when NEXT/STEP exits
-                        if name == "next" || name == "step" {
-                            // Get FOR/START instruction
-                            if let Some(Object::Branch { args: for_args, .. }) =
-                                objects.get(args_mut.arg1)
-                            {
-                                // Only FOR loops have loop variables (arg1 != STEP_OUT)
-                                if for_args.arg1 != STEP_OUT {
-                                    if let Some(Object::Symbol { name: var_name, .. }) =
-                                        objects.get(for_args.arg1)
-                                    {
-                                        ctx.local_heap.purge(var_name);
-                                    }
-                                }
-                            }
-                        }
-                        ip += 1; // Continue to next instruction
-                    }
-                    RT_ERROR => {
-                        return Err(Error::RuntimeError("branch execution error".to_string()));
-                    }
-                    _ => {
-                        // Special handling for NEXT/STEP: jump to FOR's arg1 + 1
-                        if name == "next" || name == "step" {
-                            // next_ip is the FOR/START index
-                            if let Some(Object::Branch { args: for_args, .. }) =
-                                objects.get(next_ip)
-                            {
-                                if for_args.arg1 != STEP_OUT {
-                                    // FOR loop: jump to symbol + 1 (start of loop body)
-                                    ip = for_args.arg1 + 1;
-                                } else {
-                                    // START loop: jump to START + 1
-                                    ip = next_ip + 1;
-                                }
-                            } else {
-                                ip = next_ip; // Fallback
-                            }
-                        } else {
-                            ip = next_ip; // Normal jump
-                        }
-                    }
-                }
-            }
+    /// Object which called the program (variable name, eval, ift...), empty for the command line
+    caller: String,
+
+    /// Call depth of the program, the command line being at depth 1. The prefix code of a
+    /// program is at the depth of its body
+    depth: usize,
+
+    /// Local frames depth below which the local variables are hidden from the program, those
+    /// of the programs calling it by name
+    floor: usize,
+}
+
+impl Frame {
+    fn new(objects: Vec<Object>, spans: Vec<Span>, source: String, base_depth: usize) -> Self {
+        Self {
+            objects,
+            spans,
+            source,
+            ip: 0,
+            traps: Vec::new(),
+            base_depth,
+            then_bind: None,
+            caller: String::new(),
+            depth: 0,
+            floor: 0,
+        }
+    }
+}
+
+/// A command line suspended by HALT, with the programs it was running
+/// CONT and SST resume it, KILL abandons it
+#[derive(Debug, Clone)]
+pub struct Halted {
+    /// Frames left to run, the next object being at the ip of the last one
+    frames: Vec<Frame>,
+
+    /// Local frames depth when the command line started
+    base_depth: usize,
+
+    /// Local frames depth when the command line was suspended
+    depth: usize,
+}
+
+/// How far the frames are run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Run {
+    /// To the end, HALT suspending the frames
+    Resumable,
 
-            // Symbol with auto_eval: recall variable
-            Object::Symbol {
-                name,
-                auto_eval: true,
-            } => {
-                auto_recall(ctx, &name)?;
-                ip += 1;
+    /// To the end, HALT raising an error (programs run by a command, which can't be suspended)
+    ToEnd,
+
+    /// Up to the next object, stepping into the programs called
+    Step,
+
+    /// Up to the next object, stepping over the programs called
+    StepOver,
+}
+
+/// Execute a vector of objects, the frames being suspended in the context by HALT
+pub fn run_objects(objects: &[Object], ctx: &mut Context) -> Result<()> {
+    run_source(objects.to_vec(), Vec::new(), String::new(), ctx)
+}
+
+/// Execute the objects parsed from `source`, at `spans` in it
+fn run_source(
+    objects: Vec<Object>,
+    spans: Vec<Span>,
+    source: String,
+    ctx: &mut Context,
+) -> Result<()> {
+    let base_depth = ctx.local_depth();
+    let mut frame = Frame::new(objects, spans, source, base_depth);
+    frame.depth = ctx.call_depth + 1;
+    frame.floor = ctx.local_floor;
+    let mut frames = vec![frame];
+    match run_frames(&mut frames, ctx, Run::Resumable) {
+        Ok(true) => {
+            ctx.halted.push(Halted {
+                frames,
+                base_depth,
+                depth: ctx.local_depth(),
+            });
+            Ok(())
+        }
+        result => {
+            ctx.truncate_local_frames(base_depth);
+            result.map(|_| ())
+        }
+    }
+}
+
+/// Execute a program, binding its local variables
+/// The program sees the local variables of the program running it, in which it is written
+pub fn run_program(program: Box<Program>, ctx: &mut Context) -> Result<()> {
+    run_program_in(program, false, ctx)
+}
+
+/// Execute a program recalled from a global variable, binding its local variables
+/// The program doesn't see the local variables of the program running it
+pub fn run_global_program(program: Box<Program>, ctx: &mut Context) -> Result<()> {
+    run_program_in(program, true, ctx)
+}
+
+/// Execute a program, in a scope of its own if `global`
+fn run_program_in(program: Box<Program>, global: bool, ctx: &mut Context) -> Result<()> {
+    let base_depth = ctx.local_depth();
+    let mut frames = Vec::new();
+    let result = call(&mut frames, program, global, ctx)
+        .and_then(|()| run_frames(&mut frames, ctx, Run::ToEnd))
+        .map(|_| ());
+    ctx.truncate_local_frames(base_depth);
+    result
+}
+
+/// Resume the last halted command line, as far as `run` goes
+/// `keyword` is the resuming command, named in errors
+pub fn resume(ctx: &mut Context, run: Run, keyword: &str) -> Result<()> {
+    check_halted(ctx, keyword)?;
+    let mut halted = ctx.halted.pop().unwrap();
+    match run_frames(&mut halted.frames, ctx, run) {
+        Ok(true) => {
+            halted.depth = ctx.local_depth();
+            ctx.halted.push(halted);
+            Ok(())
+        }
+        result => {
+            ctx.truncate_local_frames(halted.base_depth);
+            result.map(|_| ())
+        }
+    }
+}
+
+/// Abandon the halted command lines, dropping their local variables
+pub fn kill(ctx: &mut Context) -> Result<()> {
+    check_halted(ctx, "kill")?;
+    let base_depth = ctx.halted[0].base_depth;
+    ctx.truncate_local_frames(base_depth);
+    ctx.halted.clear();
+    Ok(())
+}
+
+/// Check that a command line is halted, its local variables being the innermost ones
+fn check_halted(ctx: &Context, keyword: &str) -> Result<()> {
+    match ctx.halted.last() {
+        None => Err(Error::RuntimeError("no halted program".to_string())),
+        Some(halted) if halted.depth != ctx.local_depth() => Err(Error::RuntimeError(format!(
+            "'{}' with local variables defined since 'halt'",
+            keyword
+        ))),
+        Some(_) => Ok(()),
+    }
+}
+
+/// The objects of the innermost program of the last halted command line, the next one to run
+/// being marked with '▸'
+pub fn halted_display(ctx: &Context) -> Option<String> {
+    let frame = ctx.halted.last()?.frames.last()?;
+    let words: Vec<String> = frame
+        .objects
+        .iter()
+        .enumerate()
+        .map(|(i, obj)| {
+            let word = obj.display(&ctx.config);
+            if i == frame.ip {
+                format!("▸{}", word)
+            } else {
+                word
             }
+        })
+        .collect();
+    Some(words.join(" "))
+}
+
+/// Run the frames until the last one ends, or as far as `run` goes
+/// Return whether the frames are suspended, by HALT or at the end of a step
+/// Programs called from a frame are pushed as new frames rather than run recursively, so that
+/// the depth of user recursion is only limited by `maxdepth`
+fn run_frames(frames: &mut Vec<Frame>, ctx: &mut Context, run: Run) -> Result<bool> {
+    if ctx.nested_runs >= MAX_NESTED_RUNS {
+        return Err(Error::RuntimeError(format!(
+            "maximum depth {} of programs run by keywords exceeded",
+            MAX_NESTED_RUNS
+        )));
+    }
+
+    // Programs run by the keywords of the frames go on from their depth and their scope
+    let call_depth = ctx.call_depth;
+    let local_floor = ctx.local_floor;
+    ctx.nested_runs += 1;
+    let result = run_nested_frames(frames, ctx, run);
+    ctx.nested_runs -= 1;
+    ctx.call_depth = call_depth;
+    ctx.local_floor = local_floor;
+    result
+}
 
-            // Program marked for auto-evaluation: evaluate it inline
-            Object::Program(ref program) if program.auto_eval => {
-                // This is a program with local variable binding
-                // Execute it inline rather than pushing it
+/// Run the frames as run_frames does, setting the call depth and the visible local variables
+/// of the object being run
+fn run_nested_frames(frames: &mut Vec<Frame>, ctx: &mut Context, run: Run) -> Result<bool> {
+    let depth = frames.len();
+    let mut stepped = false;
 
-                // Execute prefix code
-                if !program.prefix_objects.is_empty() {
-                    run_objects(&program.prefix_objects, ctx)?;
+    // Index of the object whose run failed in the last frame, None if the error was raised
+    // calling a program or binding its local variables
+    let mut failed_at = None;
+
+    while !frames.is_empty() {
+        if INTERRUPTED.swap(false, Ordering::SeqCst) {
+            return Err(Error::AbortCurrentEntry);
+        }
+
+        let level = frames.len();
+        let frame = frames.last_mut().unwrap();
+        ctx.local_floor = frame.floor;
+
+        let result = if frame.ip >= frame.objects.len() {
+            // End of frame: drop its local variables, then go on with its caller
+            let frame = frames.pop().unwrap();
+            ctx.truncate_local_frames(frame.base_depth);
+            match frame.then_bind {
+                Some(program) => bind_locals(
+                    frames,
+                    *program,
+                    ctx.local_depth(),
+                    frame.caller,
+                    frame.depth,
+                    frame.floor,
+                    ctx,
+                ),
+                None => Ok(()),
+            }
+        } else if stepped && (run == Run::Step || (run == Run::StepOver && level <= depth)) {
+            // Step done, stopping before the next object
+            return Ok(true);
+        } else if run != Run::ToEnd
+            && matches!(
+                frame.objects[frame.ip],
+                Object::Keyword { name: "halt", .. }
+            )
+        {
+            ctx.not_repeatable = true;
+            frame.ip += 1;
+            return Ok(true);
+        } else {
+            stepped = true;
+            let at = frame.ip;
+            ctx.call_depth = frame.depth;
+            match run_instrumented(frame, ctx) {
+                Ok(Some(program)) => {
+                    // Called by the name of a global variable
+                    let global = matches!(
+                        &frame.objects[at],
+                        Object::Symbol { name, .. } if !ctx.is_local(name)
+                    );
+                    call(frames, program, global, ctx)
+                }
+                Ok(None) => Ok(()),
+                Err(e) => {
+                    failed_at = Some(at);
+                    Err(e)
                 }
+            }
+        };
 
-                // Bind local variables if present
-                if !program.local_vars.is_empty() {
-                    let num_vars = program.local_vars.len();
-                    if ctx.stack.len() < num_vars {
-                        return Err(Error::MissingOperand);
-                    }
+        if let Err(e) = result {
+            trap(frames, e, failed_at.take(), ctx)?;
+        }
+    }
 
-                    let mut values = Vec::new();
-                    for _ in 0..num_vars {
-                        values.push(ctx.stack.pop().unwrap());
-                    }
-                    values.reverse();
+    Ok(false)
+}
 
-                    for (var_name, value) in program.local_vars.iter().zip(values.iter()) {
-                        ctx.local_heap.store(var_name.clone(), value.clone());
-                    }
+/// Calls and instructions counted by PROFILE
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// Number of objects run
+    pub instructions: u64,
 
-                    // Execute body
-                    run_objects(&program.objects, ctx)?;
+    /// Number of calls and cumulative time of each keyword
+    pub keywords: HashMap<&'static str, (u64, Duration)>,
+}
 
-                    // Clean up local variables
-                    for var_name in &program.local_vars {
-                        ctx.local_heap.purge(var_name);
-                    }
-                } else {
-                    // No local vars, just execute body
-                    run_objects(&program.objects, ctx)?;
-                }
+/// Run the object at the ip of `frame`, tracing it and profiling it when asked to
+fn run_instrumented(frame: &mut Frame, ctx: &mut Context) -> Result<Option<Box<Program>>> {
+    if !ctx.config.trace && ctx.profile.is_none() {
+        return run_object(&mut frame.objects, &mut frame.ip, &mut frame.traps, ctx);
+    }
+
+    let obj = &frame.objects[frame.ip];
+    let name = match obj {
+        Object::Keyword { name, .. } | Object::Branch { name, .. } => Some(*name),
+        _ => None,
+    };
+    let word = ctx.config.trace.then(|| obj.display(&ctx.config));
+
+    let start = Instant::now();
+    let result = run_object(&mut frame.objects, &mut frame.ip, &mut frame.traps, ctx);
+    let elapsed = start.elapsed();
+
+    if let Some(profile) = &mut ctx.profile {
+        profile.instructions += 1;
+        if let Some(name) = name {
+            let (calls, time) = profile.keywords.entry(name).or_default();
+            *calls += 1;
+            *time += elapsed;
+        }
+    }
+    if let Some(word) = word {
+        // Object run, stack depth and top values, top last
+        ctx.not_repeatable = true;
+        let mut top: Vec<String> = ctx
+            .stack
+            .iter()
+            .take(TRACE_VALUES)
+            .map(|obj| obj.display(&ctx.config))
+            .collect();
+        top.reverse();
+        let more = if ctx.stack.len() > TRACE_VALUES {
+            "... "
+        } else {
+            ""
+        };
+        println!(
+            "trace: {:<12} {}: {}{}",
+            word,
+            ctx.stack.len(),
+            more,
+            top.join(" ")
+        );
+    }
+
+    result
+}
 
-                ip += 1;
+/// Resume in the error clause of the innermost IFERR being run, leaving the frames above it
+/// Return the error if no IFERR traps it, its traceback being set as the error context
+fn trap(
+    frames: &mut Vec<Frame>,
+    e: Error,
+    failed_at: Option<usize>,
+    ctx: &mut Context,
+) -> Result<()> {
+    if e.is_trappable() && frames.iter().any(|frame| !frame.traps.is_empty()) {
+        while let Some(frame) = frames.last_mut() {
+            if let Some((iferr_index, error_ip, depth)) = frame.traps.pop() {
+                // Resume in the error clause, ELSE being skipped after it
+                reset_loops(&mut frame.objects[iferr_index..error_ip]);
+                ctx.truncate_local_frames(depth);
+                if let Object::Branch { args, .. } = &mut frame.objects[iferr_index] {
+                    args.condition = 1;
+                }
+                ctx.last_error = Some(e);
+                ctx.clear_error_context();
+                frame.ip = error_ip;
+                return Ok(());
             }
+            frames.pop();
+        }
+    }
+
+    report_error(frames, failed_at, ctx);
+    Err(e)
+}
+
+/// Set the error context to the traceback of `frames`, outermost first: the source line of
+/// each program with a caret under the object being run, the call to the next program or the
+/// failing object. The traceback already set by the programs run by a keyword comes last
+/// A program called at the end of its caller replaced it, the caller doesn't show
+fn report_error(frames: &[Frame], failed_at: Option<usize>, ctx: &mut Context) {
+    let inner = ctx.error_context.take();
+    let mut entries: Vec<(String, usize)> = Vec::new();
+
+    for (level, frame) in frames.iter().enumerate() {
+        let index = match failed_at {
+            Some(at) if level + 1 == frames.len() => at,
+            _ => frame.ip.saturating_sub(1),
+        };
+        let Some((line, column, width)) = locate(frame, index, ctx) else {
+            continue;
+        };
+
+        let label = match frame.caller.as_str() {
+            "" => String::new(),
+            caller => format!("{}: ", caller),
+        };
+        let entry = format!(
+            "  {}{}\n  {}{}",
+            label,
+            line,
+            " ".repeat(label.chars().count() + column),
+            "^".repeat(width)
+        );
+        entries.push((entry, 0));
+    }
+    if let Some(inner) = &inner {
+        entries.extend(traceback_entries(inner));
+    }
+
+    // Recursive calls show once, also through programs run by keywords
+    let mut lines: Vec<String> = Vec::new();
+    let mut previous: Option<String> = None;
+    let mut repeated = 0;
+    for (entry, times) in entries {
+        if previous.as_ref() == Some(&entry) {
+            repeated += times + 1;
+            continue;
+        }
+        if repeated > 0 {
+            lines.push(format!("  ... repeated {} more times", repeated));
+        }
+        lines.push(entry.clone());
+        previous = Some(entry);
+        repeated = times;
+    }
+    if repeated > 0 {
+        lines.push(format!("  ... repeated {} more times", repeated));
+    }
+
+    if !lines.is_empty() {
+        ctx.set_error_context(lines.join("\n"));
+    }
+}
 
-            // Everything else: push to stack
+/// The entries of a traceback set by report_error, with the number of times each one is
+/// repeated after it
+fn traceback_entries(traceback: &str) -> Vec<(String, usize)> {
+    let mut entries: Vec<(String, usize)> = Vec::new();
+    let mut lines = traceback.lines().peekable();
+    while let Some(line) = lines.next() {
+        let times = line
+            .trim_start()
+            .strip_prefix("... repeated ")
+            .and_then(|rest| rest.strip_suffix(" more times"))
+            .and_then(|n| n.parse::<usize>().ok());
+        match (times, entries.last_mut()) {
+            (Some(times), Some(last)) => last.1 += times,
             _ => {
-                ctx.stack.push(obj);
-                ip += 1;
+                let mut entry = line.to_string();
+                if let Some(carets) =
+                    lines.next_if(|next| next.chars().all(|c| c == ' ' || c == '^'))
+                {
+                    entry.push('\n');
+                    entry.push_str(carets);
+                }
+                entries.push((entry, 0));
             }
         }
     }
+    entries
+}
 
+/// The source line of the object at `index` in `frame`, with the column and the width of the
+/// object in it
+/// Frames without source show their objects as displayed
+fn locate(frame: &Frame, index: usize, ctx: &Context) -> Option<(String, usize, usize)> {
+    let chars: Vec<char> = frame.source.chars().collect();
+    let (line, column, width) = match frame.spans.get(index) {
+        Some(span) if span.end <= chars.len() && span.start < span.end => {
+            let line_start = chars[..span.start]
+                .iter()
+                .rposition(|&c| c == '\n')
+                .map_or(0, |i| i + 1);
+            let line_end = chars[span.start..]
+                .iter()
+                .position(|&c| c == '\n')
+                .map_or(chars.len(), |i| span.start + i);
+            (
+                chars[line_start..line_end].to_vec(),
+                span.start - line_start,
+                span.end.min(line_end) - span.start,
+            )
+        }
+        _ => {
+            frame.objects.get(index)?;
+            let words: Vec<String> = frame
+                .objects
+                .iter()
+                .map(|obj| obj.display(&ctx.config))
+                .collect();
+            let column = words[..index].iter().map(|w| w.chars().count() + 1).sum();
+            let line: Vec<char> = words.join(" ").chars().collect();
+            (line, column, words[index].chars().count())
+        }
+    };
+
+    // Program sources have spaces around their objects
+    let indent = line[..column]
+        .iter()
+        .take_while(|c| c.is_whitespace())
+        .count();
+    let end = line
+        .iter()
+        .rposition(|c| !c.is_whitespace())
+        .map_or(0, |i| i + 1);
+    let line = &line[indent..end.max(column + width)];
+    let column = column - indent;
+
+    // Long lines are cut around the object
+    if line.len() <= REPORT_WIDTH {
+        return Some((line.iter().collect(), column, width.max(1)));
+    }
+    let first = column
+        .saturating_sub(REPORT_WIDTH / 3)
+        .min(line.len() - REPORT_WIDTH);
+    let last = first + REPORT_WIDTH;
+    let mut text = String::new();
+    let mut column = column - first;
+    if first > 0 {
+        text.push_str("...");
+        column += 3;
+    }
+    text.extend(&line[first..last]);
+    if last < line.len() {
+        text.push_str("...");
+    }
+    Some((text, column, width.clamp(1, REPORT_WIDTH)))
+}
+
+/// Push the frames running `program` above its caller
+/// A program recalled from a global variable (`global`) only sees its own local variables,
+/// the others see those of the program running them, in which they are written
+fn call(
+    frames: &mut Vec<Frame>,
+    program: Box<Program>,
+    global: bool,
+    ctx: &mut Context,
+) -> Result<()> {
+    let caller = match frames.last() {
+        Some(frame) => match frame.objects.get(frame.ip.wrapping_sub(1)) {
+            Some(obj) => obj.display(&ctx.config),
+            None => String::new(),
+        },
+        None => String::new(),
+    };
+
+    // A program called at the end of its caller replaces it (tail call), unless the caller is
+    // the first frame, kept for error reports. The local variables of the caller are dropped
+    // when the program ends
+    let mut base_depth = ctx.local_depth();
+    if let [_, .., caller] = frames.as_slice() {
+        if caller.then_bind.is_none()
+            && caller.traps.is_empty()
+            && program.prefix_objects.is_empty()
+            && ends_at(&caller.objects, caller.ip)
+        {
+            base_depth = caller.base_depth;
+            frames.pop();
+        }
+    }
+
+    // A global program hides the local variables below it, those of a tail caller being
+    // dropped right away so that tail recursion runs in constant space
+    let floor = if global {
+        ctx.truncate_local_frames(base_depth);
+        base_depth
+    } else {
+        ctx.local_floor
+    };
+
+    // Programs are counted once whatever the frames running them, and programs run by
+    // keywords count from the object running them
+    let depth = frames.last().map_or(ctx.call_depth, |frame| frame.depth) + 1;
+    if depth > ctx.config.max_depth {
+        return Err(Error::RuntimeError(format!(
+            "maximum depth {} exceeded",
+            ctx.config.max_depth
+        )));
+    }
+
+    let level = frames.len();
+    if program.local_vars.is_empty() {
+        // No local vars: body run after prefix code
+        let Program {
+            objects,
+            spans,
+            value,
+            prefix_objects,
+            prefix_spans,
+            ..
+        } = *program;
+        if prefix_objects.is_empty() {
+            frames.push(Frame::new(objects, spans, value, base_depth));
+        } else {
+            frames.push(Frame::new(objects, spans, value.clone(), base_depth));
+            frames.push(Frame::new(prefix_objects, prefix_spans, value, base_depth));
+        }
+        for frame in &mut frames[level..] {
+            frame.caller = caller.clone();
+            frame.depth = depth;
+            frame.floor = floor;
+        }
+        Ok(())
+    } else if program.prefix_objects.is_empty() {
+        bind_locals(frames, *program, base_depth, caller, depth, floor, ctx)
+    } else {
+        // Prefix code computing the values of the local variables
+        let mut frame = Frame::new(
+            program.prefix_objects.clone(),
+            program.prefix_spans.clone(),
+            program.value.clone(),
+            base_depth,
+        );
+        frame.then_bind = Some(program);
+        frame.caller = caller;
+        frame.depth = depth;
+        frame.floor = floor;
+        frames.push(frame);
+        Ok(())
+    }
+}
+
+/// Bind the local variables of `program` to the values on the stack in a new local frame, and
+/// push the frame running its body, called by `caller` at call depth `depth` and seeing the
+/// local frames from `floor`, dropping the local frames from `base_depth` when it ends
+fn bind_locals(
+    frames: &mut Vec<Frame>,
+    program: Program,
+    base_depth: usize,
+    caller: String,
+    depth: usize,
+    floor: usize,
+    ctx: &mut Context,
+) -> Result<()> {
+    let num_vars = program.local_vars.len();
+    if ctx.stack.len() < num_vars {
+        return Err(Error::MissingOperand);
+    }
+
+    // With stack [a, b, c] (c on top) and vars [x, y], x gets b and y gets c
+    let mut values = Vec::new();
+    for _ in 0..num_vars {
+        values.push(ctx.stack.pop().unwrap());
+    }
+    values.reverse();
+
+    let mut locals = Heap::new();
+    for (var_name, value) in program.local_vars.iter().zip(values) {
+        locals.store(var_name.clone(), value);
+    }
+
+    ctx.push_local_frame(locals);
+    let mut frame = Frame::new(program.objects, program.spans, program.value, base_depth);
+    frame.caller = caller;
+    frame.depth = depth;
+    frame.floor = floor;
+    frames.push(frame);
     Ok(())
 }
 
-/// Auto-recall a variable and push it to the stack
-/// If variable doesn't exist, push the symbol itself
-/// If the variable contains a Program, execute it (standard RPL behavior)
-fn auto_recall(ctx: &mut Context, name: &str) -> Result<()> {
-    if let Some(obj) = ctx.find_variable(name) {
-        // Clone the object to avoid borrow issues
-        let obj = obj.clone();
+/// Whether nothing is left to run from `ip` but RETURN, and ELSEs and ENDs jumping forward
+fn ends_at(objects: &[Object], mut ip: usize) -> bool {
+    use crate::operations::branch::STEP_OUT;
 
-        // Variable exists
+    while let Some(obj) = objects.get(ip) {
         match obj {
-            Object::Program(program) => {
-                // Programs are auto-executed when recalled (standard RPL behavior)
-                // Execute prefix code if present
-                if !program.prefix_objects.is_empty() {
-                    run_objects(&program.prefix_objects, ctx)?;
+            Object::Branch { name: "return", .. } => return true,
+            Object::Branch {
+                name: "else", args, ..
+            } => ip = args.arg2,
+            Object::Branch {
+                name: "end", args, ..
+            } if args.arg1 == STEP_OUT => {
+                if args.arg2 == STEP_OUT {
+                    ip += 1;
+                } else if args.arg2 > ip {
+                    ip = args.arg2;
+                } else {
+                    return false;
                 }
+            }
+            _ => return false,
+        }
+    }
 
-                // Handle local variables if present
-                if !program.local_vars.is_empty() {
-                    let num_vars = program.local_vars.len();
-                    if ctx.stack.len() < num_vars {
-                        return Err(Error::MissingOperand);
-                    }
+    true
+}
 
-                    let mut values = Vec::new();
-                    for _ in 0..num_vars {
-                        values.push(ctx.stack.pop().unwrap());
-                    }
-                    values.reverse();
+/// Reset the counters of the loops left early, so that they start over when run again
+fn reset_loops(objects: &mut [Object]) {
+    for obj in objects {
+        if let Object::Branch { name, args, .. } = obj {
+            if *name == "next" || *name == "step" {
+                args.arg_bool = false;
+                args.iteration_count = 0;
+            }
+        }
+    }
+}
+
+/// Execute the object at `ip` and move `ip` to the next one to run
+/// Return the program to call, if any
+fn run_object(
+    objects: &mut [Object],
+    ip: &mut usize,
+    traps: &mut Vec<(usize, usize, usize)>,
+    ctx: &mut Context,
+) -> Result<Option<Box<Program>>> {
+    use crate::operations::branch::{loop_value, RT_ERROR, STEP_OUT};
+
+    // Clone necessary data to avoid borrow conflicts
+    let obj = objects[*ip].clone();
+
+    match obj {
+        // EVAL of a program: call it
+        Object::Keyword { name: "eval", .. }
+            if matches!(ctx.stack.get(0), Some(Object::Program(_))) =>
+        {
+            *ip += 1;
+            if let Some(Object::Program(program)) = ctx.stack.pop() {
+                return Ok(Some(program));
+            }
+        }
+
+        // Keyword: call handler function
+        Object::Keyword { name, handler } => {
+            if ctx.config.verified && NOT_REPEATABLE.contains(&name) {
+                ctx.not_repeatable = true;
+            }
+            handler(ctx)?;
+            *ip += 1;
+        }
+
+        // IFT/IFTE: push the value chosen by the condition, or call it if it is a program
+        Object::Branch { name, .. } if name == "ift" || name == "ifte" => {
+            let value = if name == "ift" {
+                crate::operations::branch::ift_value(ctx)?
+            } else {
+                Some(crate::operations::branch::ifte_value(ctx)?)
+            };
+            *ip += 1;
+            match value {
+                Some(Object::Program(program)) => return Ok(Some(program)),
+                Some(value) => ctx.stack.push(value),
+                None => {}
+            }
+        }
+
+        // Branch: call branch handler (control flow)
+        Object::Branch {
+            handler,
+            args,
+            name,
+        } => {
+            let mut args_mut = args.clone();
+
+            // Special handling for ELSE: copy condition from IF (which was set by THEN)
+            if name == "else" {
+                let if_index = args.arg3;
+                if let Some(Object::Branch { args: if_args, .. }) = objects.get(if_index) {
+                    args_mut.condition = if_args.condition;
+                }
+            }
+
+            // Special handling for NEXT/STEP: copy loop bounds from FOR/START on first iteration
+            if (name == "next" || name == "step") && !args_mut.arg_bool {
+                if let Some(Object::Branch { args: for_args, .. }) = objects.get(args_mut.arg1) {
+                    args_mut.first_index = for_args.first_index.clone();
+                    args_mut.last_index = for_args.last_index.clone();
+                }
+            }
+
+            let next_ip = handler(ctx, &mut args_mut)?;
+
+            // Special handling for IFERR: trap errors up to THEN, handled from THEN+1
+            if name == "iferr" {
+                traps.push((*ip, args_mut.arg1, ctx.local_depth()));
+            }
+
+            // Special handling for THEN of IFERR: the trap clause ran without error
+            if name == "then" && args_mut.arg_bool {
+                traps.pop();
+            }
 
-                    for (var_name, value) in program.local_vars.iter().zip(values.iter()) {
-                        ctx.local_heap.store(var_name.clone(), value.clone());
+            // Special handling for BREAK/CONTINUE/RETURN: drop the error traps set in the loops left,
+            // BREAK also resets the loop and drops the frame of a FOR loop variable
+            if name == "break" || name == "continue" {
+                traps.retain(|&(iferr_index, _, _)| iferr_index < args.arg2);
+                if name == "break" {
+                    reset_loops(&mut objects[args.arg2..=args.arg3]);
+                    if let Some(Object::Branch { name: "for", .. }) = objects.get(args.arg2) {
+                        ctx.pop_local_frame();
                     }
+                }
+            }
+            if name == "return" {
+                traps.clear();
+            }
 
-                    // Execute body
-                    run_objects(&program.objects, ctx)?;
+            // Special handling for THEN: store condition back to IF so ELSE can read it
+            if name == "then" {
+                let if_index = args.arg3;
+                if let Some(Object::Branch { args: if_args, .. }) = objects.get_mut(if_index) {
+                    if_args.condition = args_mut.condition;
+                }
+            }
+
+            // Special handling for FOR: initialize loop variable in a new local frame
+            if name == "for" && args_mut.arg1 != STEP_OUT && next_ip == args_mut.arg1 + 1 {
+                // Get loop variable symbol
+                if let Some(Object::Symbol { name: var_name, .. }) = objects.get(args_mut.arg1) {
+                    use rug::Float;
+                    let initial_value = Object::Number {
+                        value: Float::with_val(ctx.config.precision_bits, &args_mut.first_index),
+                        base: 10,
+                    };
+                    let mut frame = Heap::new();
+                    frame.store(var_name.clone(), initial_value);
+                    ctx.push_local_frame(frame);
+                }
+            }
 
-                    // Clean up local variables
-                    for var_name in &program.local_vars {
-                        ctx.local_heap.purge(var_name);
+            // Special handling for NEXT/STEP: update loop variable
+            if (name == "next" || name == "step") && args_mut.arg1 != STEP_OUT {
+                // Get FOR/START index
+                if let Some(Object::Branch { args: for_args, .. }) = objects.get(args_mut.arg1) {
+                    if for_args.arg1 != STEP_OUT {
+                        // This is a FOR loop (not START)
+                        if let Some(Object::Symbol { name: var_name, .. }) =
+                            objects.get(for_args.arg1)
+                        {
+                            // Update loop variable in the loop frame, computed from the iteration
+                            // count to avoid accumulation errors
+                            let new_value = Object::Number {
+                                value: loop_value(&args_mut, ctx.config.precision_bits),
+                                base: 10,
+                            };
+                            if let Some(frame) = ctx.local_frames.last_mut() {
+                                frame.store(var_name.clone(), new_value);
+                            }
+                        }
                     }
-                } else {
-                    // No local vars, just execute body
-                    run_objects(&program.objects, ctx)?;
                 }
+            }
 
-                Ok(())
+            // Update args in objects array (for stateful branches like FOR/NEXT)
+            if let Object::Branch { args: obj_args, .. } = &mut objects[*ip] {
+                *obj_args = args_mut.clone();
             }
-            _ => {
-                // Not a program, just push the value
-                ctx.stack.push(obj);
-                Ok(())
+
+            match next_ip {
+                STEP_OUT => {
+                    // Clean up loop variable frame when NEXT/STEP exits
+                    if name == "next" || name == "step" {
+                        // Get FOR/START instruction
+                        if let Some(Object::Branch { args: for_args, .. }) =
+                            objects.get(args_mut.arg1)
+                        {
+                            // Only FOR loops have loop variables (arg1 != STEP_OUT)
+                            if for_args.arg1 != STEP_OUT {
+                                ctx.pop_local_frame();
+                            }
+                        }
+                    }
+                    *ip += 1; // Continue to next instruction
+                }
+                RT_ERROR => {
+                    return Err(Error::RuntimeError("branch execution error".to_string()));
+                }
+                _ => {
+                    // Special handling for NEXT/STEP: jump to FOR's arg1 + 1
+                    if name == "next" || name == "step" {
+                        // next_ip is the FOR/START index
+                        if let Some(Object::Branch { args: for_args, .. }) = objects.get(next_ip) {
+                            if for_args.arg1 != STEP_OUT {
+                                // FOR loop: jump to symbol + 1 (start of loop body)
+                                *ip = for_args.arg1 + 1;
+                            } else {
+                                // START loop: jump to START + 1
+                                *ip = next_ip + 1;
+                            }
+                        } else {
+                            *ip = next_ip; // Fallback
+                        }
+                    } else {
+                        *ip = next_ip; // Normal jump
+                    }
+                }
             }
         }
-    } else {
-        // Variable doesn't exist, push the symbol itself
-        ctx.stack.push(Object::Symbol {
-            name: name.to_string(),
-            auto_eval: false,
-        });
-        Ok(())
+
+        // Symbol with auto_eval: recall variable, calling it if it is a program
+        Object::Symbol {
+            name,
+            auto_eval: true,
+        } => {
+            *ip += 1;
+            return Ok(auto_recall(ctx, &name));
+        }
+
+        // Program marked for auto-evaluation: call it inline
+        Object::Program(program) if program.auto_eval => {
+            *ip += 1;
+            return Ok(Some(program));
+        }
+
+        // Everything else: push to stack
+        _ => {
+            ctx.stack.push(obj);
+            *ip += 1;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Auto-recall a variable and push it to the stack
+/// If variable doesn't exist, push the symbol itself
+/// If the variable contains a Program, return it to be executed (standard RPL behavior)
+fn auto_recall(ctx: &mut Context, name: &str) -> Option<Box<Program>> {
+    match ctx.find_variable(name) {
+        // Programs are auto-executed when recalled (standard RPL behavior)
+        Some(Object::Program(program)) => Some(program.clone()),
+        Some(obj) => {
+            // Not a program, just push the value
+            ctx.stack.push(obj.clone());
+            None
+        }
+        None => {
+            // Variable doesn't exist, push the symbol itself
+            ctx.stack.push(Object::Symbol {
+                name: name.to_string(),
+                auto_eval: false,
+            });
+            None
+        }
     }
 }
 
@@ -349,6 +1116,10 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
         index_end: Option<usize>,
         is_do_until: bool,
         is_while_repeat: bool,
+        is_iferr: bool,
+        is_case: bool,
+        is_case_clause: bool,
+        index_case_ends: Vec<usize>,
     }
 
     impl IfLayout {
@@ -360,12 +1131,40 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                 index_end: None,
                 is_do_until: false,
                 is_while_repeat: false,
+                is_iferr: false,
+                is_case: false,
+                is_case_clause: false,
+                index_case_ends: Vec::new(),
             }
         }
     }
 
     let mut vlayout: Vec<IfLayout> = Vec::new(); // Stack of if/do/while contexts
     let mut vstart_index: Vec<usize> = Vec::new(); // Stack of start/for contexts
+    let mut vloop: Vec<(usize, Vec<usize>)> = Vec::new(); // Stack of loops and their BREAK/CONTINUE
+
+    // Link the BREAK/CONTINUE of a loop running from `start` to `end`, CONTINUE going to `next`
+    fn link_loop_exits(
+        objects: &mut [Object],
+        (start, exits): (usize, Vec<usize>),
+        end: usize,
+        next: Option<usize>,
+    ) -> Result<()> {
+        for exit in exits {
+            if let Object::Branch { name, args, .. } = &mut objects[exit] {
+                args.arg1 = if *name == "break" {
+                    end + 1
+                } else {
+                    next.ok_or_else(|| {
+                        Error::SyntaxError("'continue' in a 'step' loop".to_string())
+                    })?
+                };
+                args.arg2 = start;
+                args.arg3 = end;
+            }
+        }
+        Ok(())
+    }
 
     for i in 0..objects.len() {
         match &objects[i] {
@@ -376,6 +1175,35 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                         vlayout.push(IfLayout::new(i));
                     }
 
+                    // IFERR: create new layout context for IFERR..THEN..[ELSE]..END
+                    "iferr" => {
+                        let mut layout = IfLayout::new(i);
+                        layout.is_iferr = true;
+                        vlayout.push(layout);
+                    }
+
+                    // CASE: create new layout context for CASE..[test THEN..END]..END
+                    "case" => {
+                        let mut layout = IfLayout::new(i);
+                        layout.is_case = true;
+                        vlayout.push(layout);
+                    }
+
+                    // THEN of a CASE: start a clause ended by its own END
+                    "then" if vlayout.last().is_some_and(|layout| layout.is_case) => {
+                        let case_index = vlayout.last().unwrap().index_if_or_do_or_while;
+                        let mut layout = IfLayout::new(case_index);
+                        layout.is_case_clause = true;
+                        layout.index_then_or_until_or_repeat = Some(i);
+                        vlayout.push(layout);
+
+                        if let Object::Branch { args, .. } = &mut objects[i] {
+                            args.arg1 = i + 1; // True branch: THEN+1
+                            args.arg2 = STEP_OUT; // Will be filled by the clause END
+                            args.arg3 = case_index;
+                        }
+                    }
+
                     // THEN: link to IF
                     "then" => {
                         if vlayout.is_empty() {
@@ -404,6 +1232,16 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                         if let Object::Branch { args, .. } = &mut objects[if_index] {
                             args.arg2 = i + 1;
                         }
+
+                        // IFERR: errors jump to THEN+1, THEN itself skips the error clause
+                        if layout.is_iferr {
+                            if let Object::Branch { args, .. } = &mut objects[if_index] {
+                                args.arg1 = i + 1;
+                            }
+                            if let Object::Branch { args, .. } = &mut objects[i] {
+                                args.arg_bool = true;
+                            }
+                        }
                     }
 
                     // ELSE: link to IF and THEN
@@ -419,6 +1257,10 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                             return Err(Error::SyntaxError("'else' without 'then'".to_string()));
                         }
 
+                        if layout.is_case_clause {
+                            return Err(Error::SyntaxError("'else' in 'case'".to_string()));
+                        }
+
                         if layout.index_else.is_some() {
                             return Err(Error::SyntaxError("duplicate 'else'".to_string()));
                         }
@@ -451,11 +1293,29 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
 
                         let layout = vlayout.pop().unwrap();
 
+                        // Handle the END of a CASE clause: a false test goes on with the next
+                        // clause, the end of the clause body leaves the CASE
+                        if layout.is_case_clause {
+                            let then_index = layout.index_then_or_until_or_repeat.unwrap();
+                            if let Object::Branch { args, .. } = &mut objects[then_index] {
+                                args.arg2 = i + 1; // THEN's false branch: next clause
+                            }
+                            vlayout.last_mut().unwrap().index_case_ends.push(i);
+                        }
+                        // Handle CASE..END: clause ENDs jump after the CASE END
+                        else if layout.is_case {
+                            for &end_index in &layout.index_case_ends {
+                                if let Object::Branch { args, .. } = &mut objects[end_index] {
+                                    args.arg2 = i + 1;
+                                }
+                            }
+                        }
                         // Handle DO..UNTIL..END
-                        if layout.is_do_until {
-                            if layout.index_then_or_until_or_repeat.is_none() {
+                        else if layout.is_do_until {
+                            let Some(until_index) = layout.index_then_or_until_or_repeat else {
                                 return Err(Error::SyntaxError("'do' without 'until'".to_string()));
-                            }
+                            };
+                            link_loop_exits(objects, vloop.pop().unwrap(), i, Some(until_index))?;
                             // END needs to jump back to DO if condition is false
                             if let Object::Branch { args, .. } = &mut objects[i] {
                                 args.arg1 = layout.index_if_or_do_or_while + 1;
@@ -471,6 +1331,12 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                             }
                             let repeat_index = layout.index_then_or_until_or_repeat.unwrap();
                             let while_index = layout.index_if_or_do_or_while;
+                            link_loop_exits(
+                                objects,
+                                vloop.pop().unwrap(),
+                                i,
+                                Some(while_index + 1),
+                            )?;
 
                             // END jumps back to WHILE
                             if let Object::Branch { args, .. } = &mut objects[i] {
@@ -485,7 +1351,11 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                         // Handle IF..THEN..[ELSE]..END
                         else {
                             if layout.index_then_or_until_or_repeat.is_none() {
-                                return Err(Error::SyntaxError("'if' without 'then'".to_string()));
+                                let name = if layout.is_iferr { "iferr" } else { "if" };
+                                return Err(Error::SyntaxError(format!(
+                                    "'{}' without 'then'",
+                                    name
+                                )));
                             }
                             let then_index = layout.index_then_or_until_or_repeat.unwrap();
                             if let Some(else_index) = layout.index_else {
@@ -507,6 +1377,7 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                         let mut layout = IfLayout::new(i);
                         layout.is_do_until = true;
                         vlayout.push(layout);
+                        vloop.push((i, Vec::new()));
                     }
 
                     // UNTIL: mark in layout
@@ -532,6 +1403,7 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                         let mut layout = IfLayout::new(i);
                         layout.is_while_repeat = true;
                         vlayout.push(layout);
+                        vloop.push((i, Vec::new()));
                     }
 
                     // REPEAT: mark in layout
@@ -555,6 +1427,7 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                     // FOR/START: push to start index stack
                     "for" | "start" => {
                         vstart_index.push(i);
+                        vloop.push((i, Vec::new()));
 
                         // Set arg1 based on whether this is FOR or START
                         if *name == "for" {
@@ -580,15 +1453,35 @@ pub fn link_control_flow(objects: &mut [Object]) -> Result<()> {
                         }
 
                         let start_or_for_index = vstart_index.pop().unwrap();
+                        let is_step = *name == "step";
+                        let next = (!is_step).then_some(i);
+                        link_loop_exits(objects, vloop.pop().unwrap(), i, next)?;
 
                         // Update NEXT/STEP's arg1 to point back to FOR/START
                         if let Object::Branch { args, .. } = &mut objects[i] {
                             args.arg1 = start_or_for_index;
                         }
 
-                        // Update FOR/START's arg2 to point to NEXT/STEP
+                        // Update FOR/START's arg2 to point to NEXT/STEP, a STEP loop running at least once
                         if let Object::Branch { args, .. } = &mut objects[start_or_for_index] {
                             args.arg2 = i;
+                            args.arg_bool = is_step;
+                        }
+                    }
+
+                    // BREAK/CONTINUE: linked by the end of the innermost loop
+                    "break" | "continue" => {
+                        let Some((_, exits)) = vloop.last_mut() else {
+                            return Err(Error::SyntaxError(format!("'{}' outside a loop", name)));
+                        };
+                        exits.push(i);
+                    }
+
+                    // RETURN: jump after the last instruction, leaving all the loops
+                    "return" => {
+                        let len = objects.len();
+                        if let Object::Branch { args, .. } = &mut objects[i] {
+                            args.arg1 = len;
                         }
                     }
 
@@ -663,4 +1556,287 @@ mod tests {
 
         assert_eq!(ctx.stack.len(), 2);
     }
+
+    #[test]
+    fn test_iferr() {
+        let mut ctx = Context::new();
+
+        // The error clause runs with the trapped error, ELSE is skipped
+        execute("iferr drop then errn else 0 end", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.len(), 1);
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "2");
+        assert_eq!(ctx.last_error, Some(Error::MissingOperand));
+    }
+
+    #[test]
+    fn test_recursive_local_frames() {
+        let mut ctx = Context::new();
+
+        // Each call binds n in its own frame, the caller's n is intact after the call
+        execute(
+            "<< -> n << if n 1 <= then 1 else n 1 - fct n * end >> >> 'fct' sto 6 fct",
+            &mut ctx,
+        )
+        .unwrap();
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "720");
+        assert_eq!(ctx.local_depth(), 0);
+    }
+
+    #[test]
+    fn test_lexical_scope() {
+        let mut ctx = Context::new();
+
+        // A program called by name at the end of its caller drops the caller's n
+        execute(
+            "<< -> n << if n 0 > then n 1 - f else n depth halt end >> >> 'f' sto 100 f",
+            &mut ctx,
+        )
+        .unwrap();
+        assert_eq!(ctx.local_depth(), 1);
+
+        // A program called by name doesn't see its caller's n
+        execute(
+            "<< n >> 'inner' sto << -> n << inner >> >> 'outer' sto",
+            &mut ctx,
+        )
+        .unwrap();
+        execute("kill clear 7 outer", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "'n'");
+
+        // Programs written inside see the local variables where they are run
+        execute("clear 7 << -> n << << n 1 + >> eval >> >> eval", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "8");
+    }
+
+    #[test]
+    fn test_halt_and_step() {
+        let mut ctx = Context::new();
+
+        // HALT suspends the line with its local variables, SST runs one object at a time
+        execute("<< -> x << x halt x 1 + >> >> 'f' sto 4 f 10", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.len(), 1);
+        assert_eq!(ctx.halted.len(), 1);
+        assert_eq!(ctx.local_depth(), 1);
+        assert_eq!(halted_display(&ctx).unwrap(), "x halt ▸x 1 +");
+
+        execute("sst", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.len(), 2);
+        assert_eq!(halted_display(&ctx).unwrap(), "x halt x ▸1 +");
+
+        // CONT runs the rest of the program, then the rest of the line
+        execute("cont", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "10");
+        assert_eq!(ctx.stack.get(1).unwrap().display(&ctx.config), "5");
+        assert!(ctx.halted.is_empty());
+        assert_eq!(ctx.local_depth(), 0);
+    }
+
+    #[test]
+    fn test_profile() {
+        let mut ctx = Context::new();
+
+        // Every object is counted, keywords are timed by name
+        ctx.profile = Some(Profile::default());
+        execute("1 2 + 3 + 4 *", &mut ctx).unwrap();
+        let profile = ctx.profile.take().unwrap();
+        assert_eq!(profile.instructions, 7);
+        assert_eq!(profile.keywords["+"].0, 2);
+        assert_eq!(profile.keywords["*"].0, 1);
+        assert!(!profile.keywords.contains_key("1"));
+    }
+
+    #[test]
+    fn test_error_report() {
+        let mut ctx = Context::new();
+
+        // Caret under the failing object of the command line
+        assert!(execute("1 2 'x' sin", &mut ctx).is_err());
+        assert_eq!(
+            ctx.error_context.as_deref(),
+            Some("  1 2 'x' sin\n          ^^^")
+        );
+
+        // One line per program being run, named after its caller
+        execute(
+            "<< -> n << n 'x' sin >> >> 'f' sto << 2 f 1 + >> 'g' sto",
+            &mut ctx,
+        )
+        .unwrap();
+        assert!(execute("g", &mut ctx).is_err());
+        assert_eq!(
+            ctx.error_context.as_deref(),
+            Some(
+                "  g\n  ^\n  g: 2 f 1 +\n       ^\n  f: -> n << n 'x' sin >>\n                   ^^^"
+            )
+        );
+
+        // Trapped errors and successful lines leave no report
+        execute("iferr 'x' sin then end", &mut ctx).unwrap();
+        assert!(ctx.error_context.is_none());
+    }
+
+    #[test]
+    fn test_error_report_recursion() {
+        let mut ctx = Context::new();
+
+        // The calls of a recursive program show once, the failing call with its own caret
+        execute(
+            "<< -> n << if n 0 == then 'x' sin else n 1 - r 1 + end >> >> 'r' sto",
+            &mut ctx,
+        )
+        .unwrap();
+        assert!(execute("3 r", &mut ctx).is_err());
+        assert_eq!(
+            ctx.error_context.as_deref(),
+            Some(concat!(
+                "  3 r\n",
+                "    ^\n",
+                "  r: -> n << if n 0 == then 'x' sin else n 1 - r 1 + end >>\n",
+                "                                               ^\n",
+                "  ... repeated 2 more times\n",
+                "  r: -> n << if n 0 == then 'x' sin else n 1 - r 1 + end >>\n",
+                "                                ^^^",
+            ))
+        );
+
+        // Also through the programs run by keywords
+        execute("<< 'rt' tevel >> 'rt' sto 5 maxdepth", &mut ctx).unwrap();
+        assert!(execute("rt", &mut ctx).is_err());
+        assert_eq!(
+            ctx.error_context.as_deref(),
+            Some(concat!(
+                "  rt\n",
+                "  ^^\n",
+                "  rt: 'rt' tevel\n",
+                "           ^^^^^\n",
+                "  'rt' tevel\n",
+                "       ^^^^^\n",
+                "  ... repeated 2 more times",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_error_report_long_line() {
+        let mut ctx = Context::new();
+
+        // The line is cut to REPORT_WIDTH characters around the failing object
+        let numbers = |range: std::ops::RangeInclusive<u32>| -> String {
+            range.map(|n| n.to_string()).collect::<Vec<_>>().join(" ")
+        };
+        let source = format!("{} 'x' sin {}", numbers(1..=30), numbers(31..=60));
+        assert!(execute(&source, &mut ctx).is_err());
+        let report = ctx.error_context.unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "  ...4 25 26 27 28 29 30 'x' sin 31 32 33 34 35 36 37 38 39 40 41 42 43 44 45...",
+                "                             ^^^",
+            ]
+        );
+        assert_eq!(lines[0].len(), 2 + 3 + REPORT_WIDTH + 3);
+    }
+
+    #[test]
+    fn test_verified_display() {
+        let mut ctx = Context::new();
+        let before = ctx.clone();
+
+        // 1 is lost at 128 bits, not at 256 bits
+        execute("1e40 1 + 1e40 -", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "0");
+        let lines = verified_display("1e40 1 + 1e40 -", &before, &ctx);
+        assert_eq!(lines, vec!["1".to_string()]);
+    }
+
+    #[test]
+    fn test_input_precision() {
+        let mut ctx = Context::new();
+
+        // Numbers are read with the precision set before the line
+        execute("64 prec", &mut ctx).unwrap();
+        execute("0.1 (1,2)", &mut ctx).unwrap();
+        match (ctx.stack.get(1), ctx.stack.get(0)) {
+            (Some(Object::Number { value, .. }), Some(Object::Complex { value: z, .. })) => {
+                assert_eq!(value.prec(), 64);
+                assert_eq!(z.prec(), (64, 64));
+            }
+            _ => panic!("Expected number and complex"),
+        }
+
+        // Programs stored then keep their numbers at that precision
+        execute("<< 0.1 >> 'p' sto 256 prec p", &mut ctx).unwrap();
+        match ctx.stack.get(0) {
+            Some(Object::Number { value, .. }) => assert_eq!(value.prec(), 64),
+            _ => panic!("Expected number"),
+        }
+    }
+
+    #[test]
+    fn test_verified_display_not_repeatable() {
+        let mut ctx = Context::new();
+        execute("verify << ticks >> 'f' sto", &mut ctx).unwrap();
+
+        // A program running ticks is not re-run, its result showing as is
+        let before = before_line(&mut ctx).unwrap();
+        execute("f", &mut ctx).unwrap();
+        assert!(ctx.not_repeatable);
+        let shown = ctx.stack.get(0).unwrap().display(&ctx.config);
+        assert_eq!(verified_display("f", &before, &ctx), vec![shown]);
+
+        // The flag is cleared before the next line
+        let before = before_line(&mut ctx).unwrap();
+        assert!(!before.not_repeatable);
+        execute("drop 1 3 /", &mut ctx).unwrap();
+        assert!(!ctx.not_repeatable);
+
+        // No copy of the context when verified display is off
+        execute("noverify", &mut ctx).unwrap();
+        assert!(before_line(&mut ctx).is_none());
+    }
+    #[test]
+    fn test_interrupt() {
+        // INTERRUPTED would abort the lines of the tests run alongside, the test runs alone in
+        // a child process
+        if std::env::var_os("RPNX_TEST_INTERRUPT").is_none() {
+            let output = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "--exact",
+                    "program::tests::test_interrupt",
+                    "--test-threads=1",
+                ])
+                .env("RPNX_TEST_INTERRUPT", "1")
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stdout)
+            );
+            return;
+        }
+
+        let mut ctx = Context::new();
+        execute("1 2 3 << -> x << while 1 repeat end >> >>", &mut ctx).unwrap();
+        let Some(Object::Program(program)) = ctx.stack.pop() else {
+            panic!("Expected program");
+        };
+
+        // Ctrl-C pressed before the loop runs, the program binding x then stopping at once
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        let result = run_program(program, &mut ctx);
+        assert!(matches!(result, Err(Error::AbortCurrentEntry)));
+
+        // The local variables of the aborted program are dropped, the stack is kept
+        assert_eq!(ctx.local_depth(), 0);
+        assert!(ctx.find_variable("x").is_none());
+        assert_eq!(ctx.stack.len(), 2);
+
+        // The next line clears an interruption left after the aborted one
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        execute("+ 3 *", &mut ctx).unwrap();
+        assert_eq!(ctx.stack.get(0).unwrap().display(&ctx.config), "9");
+        assert!(!INTERRUPTED.load(Ordering::SeqCst));
+    }
 }
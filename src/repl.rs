@@ -3,6 +3,7 @@
 // Uses rustyline for line editing and history
 
 use crate::context::Context;
+use crate::number::AngleMode;
 use crate::operations::{BRANCHES, KEYWORDS};
 use crate::program;
 use rustyline::completion::{Completer, Pair};
@@ -394,6 +395,28 @@ pub fn history_path() -> PathBuf {
     data_dir.join("rpnx").join("history")
 }
 
+/// Prompt showing the angle mode when it is not radians, the modulus in modular mode, the
+/// input base when it is not decimal and whether a program is halted
+fn prompt(ctx: &Context) -> String {
+    let mut prompt = String::from("rpnx");
+    match ctx.config.angle_mode {
+        AngleMode::Rad => {}
+        AngleMode::Deg => prompt.push_str(" deg"),
+        AngleMode::Grad => prompt.push_str(" grad"),
+    }
+    if let Some(modulus) = &ctx.config.modulus {
+        prompt.push_str(&format!(" mod {}", modulus));
+    }
+    if ctx.config.input_base != 10 {
+        prompt.push_str(&format!(" ibase {}", ctx.config.input_base));
+    }
+    if !ctx.halted.is_empty() {
+        prompt.push_str(" halt");
+    }
+    prompt.push_str("> ");
+    prompt
+}
+
 /// Run the interactive REPL
 pub fn run_repl() -> RustylineResult<()> {
     let history_file = history_path();
@@ -422,7 +445,7 @@ pub fn run_repl() -> RustylineResult<()> {
 
     loop {
         // Read line
-        let readline = rl.readline("rpnx> ");
+        let readline = rl.readline(&prompt(&ctx));
         match readline {
             Ok(line) => {
                 // Reset Ctrl+C counter on any input
@@ -458,7 +481,8 @@ pub fn run_repl() -> RustylineResult<()> {
                     }
                 }
 
-                // Execute
+                // Execute, keeping the context before the line for verified display
+                let before = program::before_line(&mut ctx);
                 match program::execute(&line, &mut ctx) {
                     Ok(()) => {
                         // Success - clear last error unless preserve flag is set
@@ -467,7 +491,13 @@ pub fn run_repl() -> RustylineResult<()> {
                         } else {
                             ctx.last_error = None;
                         }
-                        show_stack(&ctx);
+                        match &before {
+                            Some(before) if ctx.config.verified => {
+                                print_stack(&program::verified_display(&line, before, &ctx));
+                                show_halted(&ctx);
+                            }
+                            _ => show_stack(&ctx),
+                        }
                     }
                     Err(crate::error::Error::Goodbye) => {
                         // Clean exit on quit/exit command
@@ -475,7 +505,7 @@ pub fn run_repl() -> RustylineResult<()> {
                     }
                     Err(crate::error::Error::Edit(content)) => {
                         // Edit mode: present content for editing
-                        let edit_result = rl.readline_with_initial("rpnx> ", (&content, ""));
+                        let edit_result = rl.readline_with_initial(&prompt(&ctx), (&content, ""));
                         match edit_result {
                             Ok(edited_line) => {
                                 // Add edited line to history
@@ -493,7 +523,7 @@ pub fn run_repl() -> RustylineResult<()> {
                                     Err(e) => {
                                         ctx.last_error = Some(e.clone());
                                         show_stack(&ctx);
-                                        eprintln!("Error: {}", e);
+                                        show_error(&ctx, &e);
                                     }
                                 }
                             }
@@ -516,7 +546,7 @@ pub fn run_repl() -> RustylineResult<()> {
                         // Save last error, show stack, then error
                         ctx.last_error = Some(e.clone());
                         show_stack(&ctx);
-                        eprintln!("Error: {}", e);
+                        show_error(&ctx, &e);
                     }
                 }
             }
@@ -554,25 +584,40 @@ pub fn run_repl() -> RustylineResult<()> {
 /// Display the current stack
 /// Top of stack (most recent) is at index 0, should display as ">" at bottom when single item
 fn show_stack(ctx: &Context) {
-    if ctx.stack.is_empty() {
-        return;
+    let lines: Vec<String> = ctx
+        .stack
+        .iter()
+        .map(|obj| obj.display(&ctx.config))
+        .collect();
+    print_stack(&lines);
+    show_halted(ctx);
+}
+
+/// Display an error, followed by its traceback if any
+fn show_error(ctx: &Context, e: &crate::error::Error) {
+    eprintln!("Error: {}", e);
+    if let Some(context) = &ctx.error_context {
+        eprintln!("{}", context);
+    }
+}
+
+/// Display the next object to run in the halted program, if any
+fn show_halted(ctx: &Context) {
+    if let Some(position) = program::halted_display(ctx) {
+        println!("halted: {}", position);
     }
+}
 
-    if ctx.stack.len() == 1 {
+/// Print displayed stack levels, top of stack first in `lines`
+fn print_stack(lines: &[String]) {
+    if lines.len() == 1 {
         // Single item: display without level number or prefix
-        if let Some(obj) = ctx.stack.get(0) {
-            let display = obj.display(&ctx.config);
-            println!("{}", highlight_line(&display));
-        }
+        println!("{}", highlight_line(&lines[0]));
     } else {
         // Multiple items: iterate from bottom (oldest) to top (newest)
         // Index i corresponds to level (i+1), so higher levels print first
-        for i in (0..ctx.stack.len()).rev() {
-            if let Some(obj) = ctx.stack.get(i) {
-                let stack_level = i + 1;
-                let display = obj.display(&ctx.config);
-                println!("{}> {}", stack_level, highlight_line(&display));
-            }
+        for (i, display) in lines.iter().enumerate().rev() {
+            println!("{}> {}", i + 1, highlight_line(display));
         }
     }
 }
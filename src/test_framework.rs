@@ -314,14 +314,21 @@ pub fn run_test_case(test: &TestCase, ctx: &mut Context) -> TestResult {
     let mut first_failure: Option<String> = None;
     let mut steps_passed = 0;
     let mut steps_failed = 0;
+    // Stack display of the last command when verified display is on
+    let mut verified: Option<Vec<String>> = None;
 
     // Process steps sequentially (interleaved commands and expectations)
     for step in &test.steps {
         match step {
             TestStep::Command(cmd) => {
                 // Execute command
+                let before = program::before_line(ctx);
+                verified = None;
                 match program::execute(cmd, ctx) {
                     Ok(()) => {
+                        if let Some(before) = before.filter(|_| ctx.config.verified) {
+                            verified = Some(program::verified_display(cmd, &before, ctx));
+                        }
                         // Respect preserve_last_error flag (set by error/strerror commands)
                         if ctx.preserve_last_error {
                             ctx.preserve_last_error = false;
@@ -338,6 +345,16 @@ pub fn run_test_case(test: &TestCase, ctx: &mut Context) -> TestResult {
             }
 
             TestStep::Expectation(expectation) => {
+                // Displayed stack, top first
+                let displayed: Vec<String> = match &verified {
+                    Some(lines) => lines.clone(),
+                    None => ctx
+                        .stack
+                        .iter()
+                        .map(|obj| obj.display(&ctx.config))
+                        .collect(),
+                };
+
                 // Verify expectation (but continue executing even if it fails)
                 let failure_msg = match expectation {
                     Expectation::StackSize(expected) => {
@@ -361,7 +378,7 @@ pub fn run_test_case(test: &TestCase, ctx: &mut Context) -> TestResult {
                                 Some(format!("Stack is empty, expected value: '{}'", expected[0]))
                             } else {
                                 // Stack level 1 is at index 0 (top of stack)
-                                let actual_value = ctx.stack.get(0).unwrap().display(&ctx.config);
+                                let actual_value = displayed[0].clone();
 
                                 if !values_match(&expected[0], &actual_value) {
                                     Some(format!(
@@ -376,11 +393,7 @@ pub fn run_test_case(test: &TestCase, ctx: &mut Context) -> TestResult {
                             // Empty or multiple values: check entire stack
                             // Stack is stored top-to-bottom, but expectations are bottom-to-top
                             // So reverse the actual stack values for comparison
-                            let mut actual: Vec<String> = ctx
-                                .stack
-                                .iter()
-                                .map(|obj| obj.display(&ctx.config))
-                                .collect();
+                            let mut actual = displayed;
                             actual.reverse();
 
                             if actual.len() != expected.len() {